@@ -6,9 +6,18 @@
 #import bevy_pbr::pbr_types
 #import bevy_pbr::utils PI
 
+struct GlowyMaterial {
+    glow_color: vec4<f32>,
+    glow_power: f32,
+    fresnel_strength: f32,
+    intensity: f32,
+};
+
 @group(1) @binding(0)
-var texture: texture_2d<f32>;
+var<uniform> material: GlowyMaterial;
 @group(1) @binding(1)
+var texture: texture_2d<f32>;
+@group(1) @binding(2)
 var texture_sampler: sampler;
 
 // fn refract(I: vec3<f32>, N: vec3<f32>, eta: f32) -> vec3<f32> {
@@ -30,12 +39,12 @@ fn fragment(in: MeshVertexOutput) -> @location(0) vec4<f32> {
     var V = normalize(view.world_position.xyz - in.world_position.xyz);
     let NdotV = max(dot(N, V), 0.0001);
     var fresnel = clamp(1.0 - NdotV, 0.0, 1.0);
-    fresnel = pow(fresnel, 5.0) * 2.0;
+    fresnel = pow(fresnel, 5.0) * material.fresnel_strength;
 
-    let glow = pow(NdotV, 10.0) * 50.0;
+    let glow = pow(NdotV, material.glow_power) * material.intensity;
     var col = vec3(0.0, 0.0, 0.0);
     
-    col = mix(col, vec3(0.5, 0.1, 0.0), glow);
+    col = mix(col, material.glow_color.rgb, glow);
 
     let bump_coords = dir_to_equirectangular(N * vec3(1.0,-0.5,1.0) - vec3(0.0,0.5,0.0));
     let bump = textureSample(texture, texture_sampler, bump_coords).r;
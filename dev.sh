@@ -1,4 +1,4 @@
 #!/bin/bash
 
 # Run in dev mode
-cargo run --features bevy/dynamic_linking,bevy/trace
\ No newline at end of file
+cargo run --features bevy/dynamic_linking,bevy/trace,dev_console
\ No newline at end of file
@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use bevy::{
+    audio::{Decodable, Source},
+    reflect::{TypePath, TypeUuid},
+};
+
+use super::synth::{Waveform, SAMPLE_RATE};
+
+/// Attack/release of each note, short enough to keep notes snappy without clicks.
+const NOTE_ENVELOPE_SECS: f32 = 0.005;
+
+/// # Music Pattern
+///
+/// Endlessly looping step sequence of notes on a single oscillator.
+/// Layers of the music are patterns sharing the same `step_secs`,
+/// started together so they stay in time.
+#[derive(Debug, Clone, TypeUuid, TypePath)]
+#[uuid = "5e0c9f3a-2d71-4b6e-8a4f-1c3b7d9e2a64"]
+pub struct MusicPattern {
+    pub waveform: Waveform,
+    pub step_secs: f32,
+    /// How long each note plays within its step, sustained if `>= step_secs`.
+    pub note_secs: f32,
+    /// Frequency (in Hz) played at each step, `None` for a rest.
+    pub notes: Vec<Option<f32>>,
+}
+
+impl Decodable for MusicPattern {
+    type DecoderItem = f32;
+    type Decoder = MusicPatternDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        MusicPatternDecoder {
+            pattern: self.clone(),
+            sample: 0,
+            phase: 0.,
+            noise: 0x2545_f491,
+        }
+    }
+}
+
+/// Sample iterator of a [`MusicPattern`].
+pub struct MusicPatternDecoder {
+    pattern: MusicPattern,
+    sample: u64,
+    phase: f32,
+    /// Xorshift state for the noise waveform.
+    noise: u32,
+}
+
+impl Iterator for MusicPatternDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let MusicPattern {
+            waveform,
+            step_secs,
+            note_secs,
+            ref notes,
+        } = self.pattern;
+        if notes.is_empty() {
+            return None;
+        }
+        let step_samples = ((step_secs * SAMPLE_RATE as f32) as u64).max(1);
+        let step = (self.sample / step_samples) as usize % notes.len();
+        let t = (self.sample % step_samples) as f32 / SAMPLE_RATE as f32;
+        self.sample += 1;
+        let Some(frequency) = notes[step] else {
+            return Some(0.);
+        };
+        if t >= note_secs {
+            return Some(0.);
+        }
+        self.phase = (self.phase + frequency / SAMPLE_RATE as f32).fract();
+        let value = waveform.sample(self.phase, &mut self.noise);
+        // envelope, sustained notes run into the next step...
+        let amplitude = if note_secs >= step_secs {
+            1.
+        } else {
+            (t / NOTE_ENVELOPE_SECS).min(1.) * ((note_secs - t) / NOTE_ENVELOPE_SECS).min(1.)
+        };
+        Some(value * amplitude)
+    }
+}
+
+impl Source for MusicPatternDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
@@ -0,0 +1,161 @@
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::{
+    audio::{Decodable, Source},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+};
+
+/// Sample rate sounds are synthesized at.
+pub const SAMPLE_RATE: u32 = 44_100;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Waveform {
+    #[default]
+    Sine,
+    Square,
+    Saw,
+    /// White noise, ignores the frequency.
+    Noise,
+}
+
+impl Waveform {
+    /// Value (`-1` to `1`) at `phase` (`0` to `1`) of the cycle,
+    /// `noise` is the xorshift state for [`Waveform::Noise`].
+    pub fn sample(&self, phase: f32, noise: &mut u32) -> f32 {
+        match self {
+            Waveform::Sine => (phase * TAU).sin(),
+            Waveform::Square => {
+                if phase < 0.5 {
+                    1.
+                } else {
+                    -1.
+                }
+            }
+            Waveform::Saw => phase * 2. - 1.,
+            Waveform::Noise => {
+                *noise ^= *noise << 13;
+                *noise ^= *noise >> 17;
+                *noise ^= *noise << 5;
+                *noise as f32 / u32::MAX as f32 * 2. - 1.
+            }
+        }
+    }
+}
+
+/// # Synth
+///
+/// Procedurally synthesized sound, a single oscillator sweeping from
+/// `frequency` to `frequency_end` with a linear attack/release envelope.
+/// Played like any other audio source, so no sound files need to be shipped.
+#[derive(Debug, Clone, TypeUuid, TypePath)]
+#[uuid = "b7d6a2c1-4f0e-4a8b-9d3e-2c5f7a1e8b90"]
+pub struct Synth {
+    pub waveform: Waveform,
+    /// Frequency (in Hz) at the start.
+    pub frequency: f32,
+    /// Frequency (in Hz) swept to by the end.
+    pub frequency_end: f32,
+    /// Length of the sound, plays forever if `None` (eg. for loops).
+    pub duration_secs: Option<f32>,
+    pub attack_secs: f32,
+    pub release_secs: f32,
+}
+
+impl Default for Synth {
+    fn default() -> Self {
+        Self {
+            waveform: Waveform::default(),
+            frequency: 440.,
+            frequency_end: 440.,
+            duration_secs: Some(0.1),
+            attack_secs: 0.005,
+            release_secs: 0.05,
+        }
+    }
+}
+
+impl Decodable for Synth {
+    type DecoderItem = f32;
+    type Decoder = SynthDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        SynthDecoder {
+            synth: self.clone(),
+            sample: 0,
+            phase: 0.,
+            noise: 0x9e37_79b9,
+        }
+    }
+}
+
+/// Sample iterator of a [`Synth`].
+pub struct SynthDecoder {
+    synth: Synth,
+    sample: u64,
+    phase: f32,
+    /// Xorshift state for the noise waveform.
+    noise: u32,
+}
+
+impl Iterator for SynthDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Synth {
+            waveform,
+            frequency,
+            frequency_end,
+            duration_secs,
+            attack_secs,
+            release_secs,
+        } = self.synth;
+        let t = self.sample as f32 / SAMPLE_RATE as f32;
+        let progress = match duration_secs {
+            Some(duration_secs) if t >= duration_secs => return None,
+            Some(duration_secs) => t / duration_secs,
+            None => 0.,
+        };
+        self.sample += 1;
+        let frequency = frequency + (frequency_end - frequency) * progress;
+        self.phase = (self.phase + frequency / SAMPLE_RATE as f32).fract();
+        let value = waveform.sample(self.phase, &mut self.noise);
+        // envelope...
+        let mut amplitude = 1.;
+        if attack_secs > 0. {
+            amplitude *= (t / attack_secs).min(1.);
+        }
+        if let (Some(duration_secs), true) = (duration_secs, release_secs > 0.) {
+            amplitude *= ((duration_secs - t) / release_secs).min(1.);
+        }
+        Some(value * amplitude)
+    }
+}
+
+impl Source for SynthDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.synth.duration_secs.map(Duration::from_secs_f32)
+    }
+}
+
+/// Handles of the synthesized sound effects.
+#[derive(Debug, Resource)]
+pub struct SoundEffects {
+    pub wall_draw: Handle<Synth>,
+    pub wall_hit: Handle<Synth>,
+    pub overheat_beep: Handle<Synth>,
+    pub game_over: Handle<Synth>,
+    pub rolling_hum: Handle<Synth>,
+}
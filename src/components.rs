@@ -1,4 +1,8 @@
 use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{resources::DifficultyResource, tuning::GameTuning};
 
 #[derive(Debug, Component)]
 pub struct RollingBall;
@@ -9,6 +13,70 @@ pub struct Ground;
 #[derive(Debug, Component)]
 pub struct GroundMesh;
 
+/// Position of a ground in the chain of grounds of a run, starting at `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct GroundIndex(pub usize);
+
+/// Layout a ground panel was spawned with.
+#[derive(Debug, Clone, Component)]
+pub struct GroundLayout {
+    /// Slope around the X axis.
+    pub angle: f32,
+    /// Sideways offset relative to the previous ground.
+    pub lateral_offset: f32,
+    pub width: f32,
+    /// Local (x, z) positions of hazards on the ground surface.
+    pub hazards: Vec<Vec2>,
+}
+
+impl GroundLayout {
+    /// Layout of the first ground of a run, flat and clear.
+    pub fn initial(tuning: &GameTuning) -> Self {
+        Self {
+            angle: tuning.ground_angle,
+            lateral_offset: 0.,
+            width: tuning.ground_width,
+            hazards: vec![],
+        }
+    }
+
+    /// Generate a random layout for the ground at `index`, only depends on the state
+    /// of `rng` and the index (and the tuning).
+    pub fn generate(
+        rng: &mut impl Rng,
+        tuning: &GameTuning,
+        GroundIndex(index): GroundIndex,
+    ) -> Self {
+        let difficulty = DifficultyResource::new(tuning, tuning.difficulty.ground_level(index));
+        let angle_variation = tuning.ground_angle_variation;
+        let angle = tuning.ground_angle
+            * difficulty.ground_angle_scale
+            * rng.gen_range((1. - angle_variation)..=(1. + angle_variation));
+        let lateral_offset =
+            rng.gen_range(-tuning.ground_lateral_offset_max..=tuning.ground_lateral_offset_max);
+        let width = rng.gen_range(tuning.ground_width_min..=tuning.ground_width)
+            * difficulty.ground_width_scale;
+        let hazards = (0..rng.gen_range(0..=tuning.ground_hazards_max))
+            .map(|_| {
+                Vec2::new(
+                    rng.gen_range(-0.35..=0.35) * width,
+                    rng.gen_range(-0.3..=0.3) * tuning.ground_length,
+                )
+            })
+            .collect();
+        Self {
+            angle,
+            lateral_offset,
+            width,
+            hazards,
+        }
+    }
+}
+
+/// Obstacle spawned on a ground as part of its [`GroundLayout`].
+#[derive(Debug, Component)]
+pub struct Hazard;
+
 #[derive(Debug, Component)]
 pub struct BelongsToGround(pub Entity);
 
@@ -18,6 +86,10 @@ pub struct GroundSurfaceSensor;
 #[derive(Debug, Component)]
 pub struct GroundMidSensor;
 
+/// Piece of a disintegrating ground, see [`Cleanup::Disintegrate`].
+#[derive(Debug, Component)]
+pub struct GroundFragment;
+
 #[derive(Debug, Component)]
 pub struct GameOverSensor {
     pub init_transform: Transform,
@@ -29,6 +101,11 @@ pub enum Cleanup {
         /// Cleanup after timer completes.
         timer: Timer,
     },
+    /// Break apart (ie. a ground into falling fragments) and fade out,
+    /// then cleanup recursively after timer completes.
+    Disintegrate {
+        timer: Timer,
+    },
     OnlyEntity,
     OnlyDescendants,
     Recursive,
@@ -40,6 +117,65 @@ pub struct Wall;
 #[derive(Debug, Component)]
 pub struct TempWall;
 
+/// Shape of a wall, cycled through with the scroll wheel while placing.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Component, Reflect, Serialize, Deserialize,
+)]
+pub enum WallKind {
+    #[default]
+    Short,
+    Long,
+    /// Curved wall, bending away from the ball.
+    Arc,
+    /// Round post pushing the ball away on contact.
+    Bumper,
+    /// Wedge the ball can roll up.
+    Ramp,
+}
+
+impl WallKind {
+    pub const ALL: [WallKind; 5] = [
+        WallKind::Short,
+        WallKind::Long,
+        WallKind::Arc,
+        WallKind::Bumper,
+        WallKind::Ramp,
+    ];
+
+    /// Kind `steps` away from this one, wrapping around.
+    pub fn cycle(self, steps: i32) -> Self {
+        let len = Self::ALL.len() as i32;
+        let index = Self::ALL.iter().position(|kind| *kind == self).unwrap_or(0) as i32;
+        Self::ALL[(index + steps).rem_euclid(len) as usize]
+    }
+}
+
+/// Looping sound following the ball's speed.
+#[derive(Debug, Component)]
+pub struct RollingHum;
+
+/// Layer of the adaptive music, faded in with the intensity of the run.
+#[derive(Debug, Component)]
+pub struct MusicLayer {
+    pub kind: MusicLayerKind,
+    /// Current level (`0` to `1`), before the layer gain and music volume.
+    pub level: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicLayerKind {
+    /// Always playing, even in the menus.
+    Pad,
+    /// Fades in over the first panels passed.
+    Bass,
+    /// Fades in as more panels are passed.
+    Arpeggio,
+    /// Follows the ball's speed.
+    Hats,
+    /// Follows how close the current panel is to overheating.
+    Alarm,
+}
+
 #[derive(Debug, Component)]
 pub struct MyLight {
     pub init_transform: Transform,
@@ -49,3 +185,14 @@ pub struct MyLight {
 pub struct MyCamera {
     pub init_transform: Transform,
 }
+
+/// Trauma-based shake of a camera, layered on top of its movement.
+#[derive(Debug, Default, Component)]
+pub struct CameraShake {
+    /// Current trauma (`0` to `1`), added by impacts and decaying over time.
+    pub trauma: f32,
+    /// Offset applied this frame, undone before the camera moves again.
+    pub applied_offset: Vec3,
+    /// Roll (in radians) applied this frame, undone with the offset.
+    pub applied_roll: f32,
+}
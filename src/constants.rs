@@ -5,3 +5,25 @@ pub const GROUND_LENGTH: f32 = 10.0;
 pub const GROUND_WIDTH: f32 = 10.0;
 pub const GROUND_THICKNESS: f32 = 0.5;
 pub const GROUND_OVERHEAT_DURATION_SECS: f32 = 5.;
+/// Remaining fraction of a ground's overheat timer below which it warns of overheating.
+pub const GROUND_OVERHEAT_WARNING_FRACTION: f32 = 0.4;
+/// Max fraction the angle of a spawned ground can vary from [`GROUND_ANGLE`].
+pub const GROUND_ANGLE_VARIATION: f32 = 0.25;
+pub const GROUND_LATERAL_OFFSET_MAX: f32 = 1.5;
+pub const GROUND_WIDTH_MIN: f32 = 7.0;
+pub const GROUND_HAZARDS_MAX: usize = 2;
+pub const GROUND_HAZARD_SIZE: f32 = 0.6;
+
+/// Contact force on the ball above which impacts shake the camera.
+pub const CAMERA_SHAKE_FORCE_THRESHOLD: f32 = 30.;
+/// Contact force on the ball of an impact adding full trauma.
+pub const CAMERA_SHAKE_FULL_FORCE: f32 = 300.;
+
+pub const HIGH_SCORES_STORAGE_KEY: &str = "high_scores";
+pub const HIGH_SCORES_MAX_ENTRIES: usize = 50;
+pub const REPLAY_STORAGE_KEY: &str = "last_replay";
+pub const SETTINGS_STORAGE_KEY: &str = "settings";
+/// Input bindings were saved under this (data) key before moving into the settings.
+pub const INPUT_ACTIONS_STORAGE_KEY: &str = "input_actions";
+/// Version of the saved settings, bump on breaking changes to them.
+pub const SETTINGS_SCHEMA_VERSION: u32 = 1;
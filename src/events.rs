@@ -1,17 +1,30 @@
 use bevy::prelude::*;
 
+use crate::components::WallKind;
+
 #[derive(Debug, Clone, Event)]
 pub enum WallEvent {
     HoverUpdate {
         ground: Entity,
         transform: Transform,
+        kind: WallKind,
     },
     HoverStop,
     Draw,
+    /// Remove a drawn wall, refunding part of its cost.
+    Remove {
+        wall: Entity,
+    },
 }
 
 #[derive(Debug, Clone, Event)]
 pub enum SceneEvent {
+    /// Load a new scene from the main menu.
     Start,
+    /// The ball was lost, end the run.
+    GameOver,
+    /// Tear down the scene and load a new one.
     Restart,
+    /// Tear down the scene and go back to the main menu.
+    Quit,
 }
@@ -0,0 +1,152 @@
+use std::fmt;
+
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
+#[cfg(feature = "dev")]
+use bevy_inspector_egui::InspectorOptions;
+use serde::{Deserialize, Serialize};
+
+/// Something the player can do, bound to an [`InputBinding`] in [`InputActions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum InputAction {
+    RotateWallCcw,
+    RotateWallCw,
+    PlaceWall,
+    RemoveWall,
+    CycleCamera,
+    CycleCameraMode,
+    Pause,
+    ToggleMute,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 8] = [
+        InputAction::RotateWallCcw,
+        InputAction::RotateWallCw,
+        InputAction::PlaceWall,
+        InputAction::RemoveWall,
+        InputAction::CycleCamera,
+        InputAction::CycleCameraMode,
+        InputAction::Pause,
+        InputAction::ToggleMute,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            InputAction::RotateWallCcw => "Rotate wall anticlockwise",
+            InputAction::RotateWallCw => "Rotate wall clockwise",
+            InputAction::PlaceWall => "Place wall",
+            InputAction::RemoveWall => "Remove wall",
+            InputAction::CycleCamera => "Cycle camera",
+            InputAction::CycleCameraMode => "Cycle camera mode",
+            InputAction::Pause => "Pause",
+            InputAction::ToggleMute => "Mute/unmute",
+        }
+    }
+}
+
+/// A key or mouse button an [`InputAction`] is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl InputBinding {
+    /// The first key or mouse button just pressed, for rebinding.
+    pub fn any_just_pressed(
+        key_input: &Input<KeyCode>,
+        mouse_btn_input: &Input<MouseButton>,
+    ) -> Option<Self> {
+        key_input
+            .get_just_pressed()
+            .next()
+            .map(|key_code| InputBinding::Key(*key_code))
+            .or_else(|| {
+                mouse_btn_input
+                    .get_just_pressed()
+                    .next()
+                    .map(|mouse_button| InputBinding::Mouse(*mouse_button))
+            })
+    }
+}
+
+impl fmt::Display for InputBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputBinding::Key(key_code) => write!(f, "{:?}", key_code),
+            InputBinding::Mouse(mouse_button) => write!(f, "Mouse {:?}", mouse_button),
+        }
+    }
+}
+
+/// # Input Actions
+///
+/// Rebindable map of [`InputAction`]s to their [`InputBinding`]s.
+#[derive(Clone, Resource, Debug, Reflect, Serialize, Deserialize)]
+#[cfg_attr(feature = "dev", derive(InspectorOptions))]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct InputActions {
+    pub bindings: HashMap<InputAction, InputBinding>,
+}
+
+impl Default for InputActions {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::from_iter([
+                (InputAction::RotateWallCcw, InputBinding::Key(KeyCode::A)),
+                (InputAction::RotateWallCw, InputBinding::Key(KeyCode::D)),
+                (
+                    InputAction::PlaceWall,
+                    InputBinding::Mouse(MouseButton::Left),
+                ),
+                (
+                    InputAction::RemoveWall,
+                    InputBinding::Mouse(MouseButton::Right),
+                ),
+                (InputAction::CycleCamera, InputBinding::Key(KeyCode::C)),
+                (InputAction::CycleCameraMode, InputBinding::Key(KeyCode::V)),
+                (InputAction::Pause, InputBinding::Key(KeyCode::Escape)),
+                (InputAction::ToggleMute, InputBinding::Key(KeyCode::M)),
+            ]),
+        }
+    }
+}
+
+impl InputActions {
+    /// Binding of an action, falling back to its default binding.
+    pub fn binding(&self, action: InputAction) -> Option<InputBinding> {
+        self.bindings
+            .get(&action)
+            .copied()
+            .or_else(|| Self::default().bindings.get(&action).copied())
+    }
+}
+
+/// Reads [`InputActions`] from the keyboard and mouse.
+#[derive(SystemParam)]
+pub struct ActionInput<'w> {
+    input_actions: Res<'w, InputActions>,
+    key_input: Res<'w, Input<KeyCode>>,
+    mouse_btn_input: Res<'w, Input<MouseButton>>,
+}
+
+impl<'w> ActionInput<'w> {
+    pub fn pressed(&self, action: InputAction) -> bool {
+        match self.input_actions.binding(action) {
+            Some(InputBinding::Key(key_code)) => self.key_input.pressed(key_code),
+            Some(InputBinding::Mouse(mouse_button)) => self.mouse_btn_input.pressed(mouse_button),
+            None => false,
+        }
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        match self.input_actions.binding(action) {
+            Some(InputBinding::Key(key_code)) => self.key_input.just_pressed(key_code),
+            Some(InputBinding::Mouse(mouse_button)) => {
+                self.mouse_btn_input.just_pressed(mouse_button)
+            }
+            None => false,
+        }
+    }
+}
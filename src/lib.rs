@@ -1,11 +1,18 @@
+mod audio;
 pub mod components;
 mod constants;
-mod events;
+pub mod events;
+pub mod input;
 mod materials;
+mod persistence;
 mod plugin;
 mod plugins;
-mod resources;
-mod state;
+pub mod resources;
+pub mod state;
 mod systems;
+pub mod tuning;
 
-pub use plugin::KeepItRollingGamePlugin;
+pub use plugin::{
+    KeepItRollingCorePlugin, KeepItRollingGamePlugin, KeepItRollingInputPlugin,
+    KeepItRollingPresentationPlugin,
+};
@@ -1,7 +1,7 @@
 use bevy::{
     prelude::*,
     reflect::{TypePath, TypeUuid},
-    render::render_resource::{AsBindGroup, ShaderRef},
+    render::render_resource::{AsBindGroup, Extent3d, ShaderRef, TextureDimension, TextureFormat},
 };
 
 /// The Material trait is very configurable, but comes with sensible defaults for all methods.
@@ -16,7 +16,71 @@ impl Material for GlowyMaterial {
 #[derive(AsBindGroup, Debug, Clone, TypeUuid, TypePath)]
 #[uuid = "717f64fe-6844-4822-8926-e0ed374294c8"]
 pub struct GlowyMaterial {
-    #[texture(0)]
-    #[sampler(1)]
+    /// Color of the inner glow.
+    #[uniform(0)]
+    pub glow_color: Color,
+    /// Exponent of the inner glow, lower spreads it further out to the edges.
+    #[uniform(0)]
+    pub glow_power: f32,
+    /// Strength of the reflection at grazing angles.
+    #[uniform(0)]
+    pub fresnel_strength: f32,
+    /// Multiplier of the inner glow.
+    #[uniform(0)]
+    pub intensity: f32,
+    /// Equirectangular env map, reflected/refracted and used as a bump map.
+    #[texture(1)]
+    #[sampler(2)]
     pub env_texture: Option<Handle<Image>>,
 }
+
+impl Default for GlowyMaterial {
+    fn default() -> Self {
+        Self {
+            glow_color: Color::rgb_linear(0.5, 0.1, 0.0),
+            glow_power: 10.,
+            fresnel_strength: 2.,
+            intensity: 50.,
+            env_texture: None,
+        }
+    }
+}
+
+/// Procedural equirectangular env map for the [`GlowyMaterial`]:
+/// a dark sky over a glowing horizon, sprinkled with stars.
+pub fn procedural_env_texture() -> Image {
+    const WIDTH: u32 = 256;
+    const HEIGHT: u32 = 128;
+    let sky = Vec3::new(0.02, 0.01, 0.08);
+    let horizon = Vec3::new(1.0, 0.35, 0.15);
+    let floor = Vec3::new(0.05, 0.0, 0.1);
+    let mut data = Vec::with_capacity((WIDTH * HEIGHT * 4) as usize);
+    for y in 0..HEIGHT {
+        // 0 at the top, 1 at the bottom...
+        let v = y as f32 / (HEIGHT - 1) as f32;
+        let horizon_glow = (1. - (v - 0.5).abs() * 8.).max(0.).powi(2);
+        let base = if v < 0.5 { sky } else { floor };
+        for x in 0..WIDTH {
+            // cheap hash for sparse stars in the sky...
+            let hash = (x.wrapping_mul(73_856_093) ^ y.wrapping_mul(19_349_663)) % 997;
+            let star = if v < 0.45 && hash < 4 { 0.8 } else { 0. };
+            let color = base.lerp(horizon, horizon_glow) + Vec3::splat(star);
+            let color = color.clamp(Vec3::ZERO, Vec3::ONE) * 255.;
+            data.extend_from_slice(&[color.x as u8, color.y as u8, color.z as u8, 255]);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: WIDTH,
+            height: HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+/// Handle of the [`procedural_env_texture`], given to [`GlowyMaterial`]s without one.
+#[derive(Debug, Resource)]
+pub struct GlowyEnvTexture(pub Handle<Image>);
@@ -0,0 +1,103 @@
+//! # Persistence
+//!
+//! Minimal string key/value storage used to save game data (and config) across sessions.
+//! Native builds write a `<key>.ron` file in the platform data (or config) dir,
+//! the wasm build uses the browser's `localStorage` for both.
+
+/// Storage key prefix, keeps our entries apart in `localStorage`.
+#[cfg(target_arch = "wasm32")]
+const KEY_PREFIX: &str = "keep-it-rolling.";
+
+#[cfg(not(target_arch = "wasm32"))]
+fn file_path(key: &str, config: bool) -> Option<std::path::PathBuf> {
+    let dirs = directories::ProjectDirs::from("me", "nilaysavant", "keep-it-rolling-game")?;
+    let dir = if config {
+        dirs.config_dir()
+    } else {
+        dirs.data_dir()
+    };
+    Some(dir.join(format!("{key}.ron")))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_file(path: std::path::PathBuf) -> Option<String> {
+    std::fs::read_to_string(path).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_file(path: std::path::PathBuf, value: &str) -> Option<()> {
+    std::fs::create_dir_all(path.parent()?).ok()?;
+    std::fs::write(path, value).ok()
+}
+
+/// Load the value stored under `key`, if any.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(key: &str) -> Option<String> {
+    load_file(file_path(key, false)?)
+}
+
+/// Store `value` under `key`, overwriting any previous value.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(key: &str, value: &str) -> Option<()> {
+    save_file(file_path(key, false)?, value)
+}
+
+/// Load the config stored under `key`, if any.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_config(key: &str) -> Option<String> {
+    load_file(file_path(key, true)?)
+}
+
+/// Store config `value` under `key`, overwriting any previous value.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_config(key: &str, value: &str) -> Option<()> {
+    save_file(file_path(key, true)?, value)
+}
+
+/// Current time as seconds since the unix epoch.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_unix_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Load the value stored under `key`, if any.
+#[cfg(target_arch = "wasm32")]
+pub fn load(key: &str) -> Option<String> {
+    local_storage()?
+        .get_item(&format!("{KEY_PREFIX}{key}"))
+        .ok()?
+}
+
+/// Store `value` under `key`, overwriting any previous value.
+#[cfg(target_arch = "wasm32")]
+pub fn save(key: &str, value: &str) -> Option<()> {
+    local_storage()?
+        .set_item(&format!("{KEY_PREFIX}{key}"), value)
+        .ok()
+}
+
+/// Load the config stored under `key`, if any.
+#[cfg(target_arch = "wasm32")]
+pub fn load_config(key: &str) -> Option<String> {
+    load(key)
+}
+
+/// Store config `value` under `key`, overwriting any previous value.
+#[cfg(target_arch = "wasm32")]
+pub fn save_config(key: &str, value: &str) -> Option<()> {
+    save(key, value)
+}
+
+/// Current time as seconds since the unix epoch.
+#[cfg(target_arch = "wasm32")]
+pub fn now_unix_secs() -> u64 {
+    (js_sys::Date::now() / 1000.) as u64
+}
@@ -1,100 +1,225 @@
-use bevy::prelude::*;
+use bevy::{audio::AddAudioSource, prelude::*, transform::TransformSystem};
 use bevy_egui::EguiPlugin;
-use bevy_inspector_egui::quick::{ResourceInspectorPlugin, WorldInspectorPlugin};
 use bevy_rapier3d::prelude::*;
 
 use crate::{
+    audio::{music::MusicPattern, synth::Synth},
     events::{SceneEvent, WallEvent},
+    input::InputActions,
     materials::glowy::GlowyMaterial,
-    plugins::{FlyCameraPlugin, FpsDisplayPlugin},
-    resources::{GroundsResource, PreviousScoresRes, ScoresResource, SettingsResource},
+    plugins::ParticlesPlugin,
+    resources::{
+        AudioMixerResource, CameraOrbitResource, CleanupSettingsResource, DifficultyResource,
+        GroundsResource, HighScoresResource, PhysicsStepsResource, ReplayResource, RunRng, RunSeed,
+        SceneLoadingTimer, ScoresResource, SettingsResource, WallBudgetResource,
+        WallPlacementResource,
+    },
     state::GameState,
     systems::{
-        camera::move_camera_focus_with_grounds,
-        cleanup::cleanup,
+        audio::{
+            display_audio_unlock_hint, play_game_over_sting, play_overheat_warning,
+            play_wall_draw_sound, play_wall_hit_sounds, setup_sounds, update_audio_mixer,
+            update_rolling_hum,
+        },
+        camera::{
+            add_camera_shake_trauma, apply_camera_projection, apply_camera_shake,
+            cycle_camera_mode, move_camera_focus_with_grounds, orbit_camera, restore_camera_shake,
+            start_camera_orbit,
+        },
+        cleanup::{cleanup, fade_disintegrating_grounds, start_ground_disintegration},
         credits::display_credits,
+        difficulty::update_difficulty,
         egui::init_egui_context,
         game_over_sensor::{
             handle_ground_game_over_sensor, move_game_over_sensors_with_current_ground,
         },
+        glow::{setup_glowy_env_texture, update_ball_glow},
         ground::{
             color_grounds,
             handle_ground_sensor,
             handle_mid_ground_sensor,
             // mark_cleanup_prev_grounds,
         },
+        high_scores::{load_high_scores, save_high_scores},
         lights::move_lighting_with_grounds,
-        menu::auto_start_game_on_menu,
-        scene::{handle_scene_events, move_to_in_game, scene_setup},
+        menu::{
+            auto_start_game_on_menu, display_game_over, display_main_menu, display_pause_menu,
+            handle_pause_input,
+        },
+        music::{setup_music, update_music_layers},
+        particles::{
+            add_ball_trails, add_ground_sparks, spawn_game_over_burst, update_ground_sparks,
+        },
+        replay::{
+            is_replay_recording, playback_wall_events, record_wall_events, reset_replay,
+            save_replay,
+        },
+        scene::{
+            count_physics_steps, handle_scene_events, move_to_in_game, pause_physics,
+            reset_physics_steps, resume_physics, scene_setup, seed_run, start_scene_loading_timer,
+        },
         scoring::{display_scoreboard, setup_scoring, update_grounds_passed, update_stopwatch},
-        settings::display_settings,
-        walls::{handle_wall_events, pick_ground_point_raycast},
+        settings::{apply_settings, display_settings, load_settings, save_settings},
+        tuning::{apply_game_tuning, load_game_tuning},
+        wall_budget::{recharge_wall_budget, reset_wall_budget},
+        wall_input::{handle_gamepad_wall_input, handle_mouse_wall_input, handle_touch_wall_input},
+        walls::{handle_bumper_contacts, handle_wall_events, pick_ground_point_raycast},
         window::setup_window,
     },
+    tuning::{GameTuning, GameTuningLoader},
 };
 
+#[cfg(feature = "dev_console")]
+use crate::plugins::DevConsolePlugin;
+#[cfg(feature = "dev")]
+use crate::plugins::{FlyCameraPlugin, FpsDisplayPlugin};
+
+/// # Keep It Rolling Game Plugin
+///
+/// The full game, ie. [`KeepItRollingCorePlugin`] with its default config
+/// plus the [`KeepItRollingPresentationPlugin`].
 pub struct KeepItRollingGamePlugin;
 
 impl Plugin for KeepItRollingGamePlugin {
     fn build(&self, app: &mut App) {
-        app //
-            // background...
-            .insert_resource(ClearColor(Color::BLACK))
-            // window...
-            .add_systems(Startup, setup_window)
-            // physics...
-            .insert_resource(RapierConfiguration {
-                timestep_mode: TimestepMode::Interpolated {
-                    dt: 1.0 / 60.0,
-                    time_scale: 1.0,
-                    substeps: 1,
-                },
-                physics_pipeline_active: false, // don't enable physics while starting
-                ..default()
-            })
-            .add_plugins((
-                RapierPhysicsPlugin::<NoUserData>::default(),
-                // RapierDebugRenderPlugin::default(),
-            ))
-            // fly cam
-            // .add_plugins(FlyCameraPlugin)
-            .add_plugins(FpsDisplayPlugin)
-            // materials
-            .add_plugins(MaterialPlugin::<GlowyMaterial>::default())
+        app // app
+            .add_plugins(KeepItRollingCorePlugin::default())
+            .add_plugins(KeepItRollingPresentationPlugin)
+            // other...
+            .add_systems(Startup, || info!("Game Started..."));
+    }
+}
+
+/// # Keep It Rolling Core Plugin
+///
+/// Gameplay only: physics, scene, grounds, walls, game over sensor and scoring.
+/// Does not need a window, egui or a renderer, so it can be driven headless:
+///
+/// ```ignore
+/// App::new()
+///     .add_plugins((
+///         MinimalPlugins,
+///         AssetPlugin::default(),
+///         ScenePlugin,
+///         TransformPlugin,
+///         HierarchyPlugin,
+///         KeepItRollingCorePlugin {
+///             timestep_mode: TimestepMode::Fixed {
+///                 dt: 1.0 / 60.0,
+///                 substeps: 1,
+///             },
+///             persistence: false,
+///             auto_start: true,
+///             disintegration: false,
+///         },
+///     ))
+///     .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(1.0 / 60.0)));
+/// ```
+pub struct KeepItRollingCorePlugin {
+    /// Rapier timestep, use a `Fixed` one to step runs deterministically.
+    pub timestep_mode: TimestepMode,
+    /// Load and save game data (eg. high scores, settings) across sessions.
+    /// Disable for headless runs so they don't touch the player's saves.
+    pub persistence: bool,
+    /// Skip the main menu and start a run right away, eg. for headless runs.
+    pub auto_start: bool,
+    /// Break overheated grounds apart before despawning them.
+    /// Disable for headless runs to skip the effect.
+    pub disintegration: bool,
+}
+
+impl Default for KeepItRollingCorePlugin {
+    fn default() -> Self {
+        Self {
+            timestep_mode: TimestepMode::Interpolated {
+                dt: 1.0 / 60.0,
+                time_scale: 1.0,
+                substeps: 1,
+            },
+            persistence: true,
+            auto_start: false,
+            disintegration: true,
+        }
+    }
+}
+
+impl Plugin for KeepItRollingCorePlugin {
+    fn build(&self, app: &mut App) {
+        // assets used by the scene, already present when rendering...
+        app.add_asset::<Mesh>()
+            .add_asset::<StandardMaterial>()
+            .add_asset::<GlowyMaterial>();
+        // tuning...
+        app.add_asset::<GameTuning>()
+            .init_asset_loader::<GameTuningLoader>()
+            .insert_resource(GameTuning::default())
+            .add_systems(Startup, load_game_tuning)
+            .add_systems(PreUpdate, apply_game_tuning);
+        // physics...
+        app.insert_resource(RapierConfiguration {
+            timestep_mode: self.timestep_mode,
+            physics_pipeline_active: false, // don't enable physics while starting
+            ..default()
+        });
+        if !app.is_plugin_added::<RapierPhysicsPlugin<NoUserData>>() {
+            app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default());
+        }
+        app // app
             // state and system sets...
             .add_state::<GameState>()
             .configure_set(
                 Update,
                 PluginSystemSet::InGame.run_if(in_state(GameState::InGame)),
             )
-            // egui
-            .add_plugins(EguiPlugin)
-            .add_systems(Startup, init_egui_context)
             // settings...
             .insert_resource(SettingsResource::default())
-            .add_systems(Update, display_settings)
-            // menu...
-            .add_systems(OnEnter(GameState::Menu), auto_start_game_on_menu)
+            .insert_resource(InputActions::default())
+            // pause and game over...
+            .add_systems(OnEnter(GameState::Paused), pause_physics)
+            .add_systems(OnExit(GameState::Paused), resume_physics)
+            .add_systems(OnEnter(GameState::GameOver), pause_physics)
             // scoring...
-            .insert_resource(PreviousScoresRes::default())
+            .insert_resource(HighScoresResource::default())
             .insert_resource(ScoresResource::default())
-            .add_systems(OnEnter(GameState::InGame), setup_scoring)
+            .add_systems(
+                OnEnter(GameState::SceneLoading),
+                (setup_scoring, update_difficulty).chain(),
+            )
             .add_systems(
                 Update,
-                (update_stopwatch, update_grounds_passed, display_scoreboard)
-                    .in_set(PluginSystemSet::InGame),
+                (update_stopwatch, update_grounds_passed).in_set(PluginSystemSet::InGame),
             )
             // scene...
             .add_event::<SceneEvent>()
-            .add_systems(OnEnter(GameState::SceneLoading), scene_setup)
+            .insert_resource(RunSeed::default())
+            .insert_resource(RunRng::default())
+            .insert_resource(ReplayResource::default())
+            .insert_resource(SceneLoadingTimer::default())
+            .insert_resource(PhysicsStepsResource::default())
             .add_systems(
-                Update,
-                move_to_in_game.run_if(in_state(GameState::SceneLoading)),
+                PostUpdate,
+                count_physics_steps.after(PhysicsSet::StepSimulation),
+            )
+            .add_systems(
+                OnEnter(GameState::SceneLoading),
+                (
+                    pause_physics,
+                    start_scene_loading_timer,
+                    reset_physics_steps,
+                    seed_run,
+                    reset_replay,
+                    scene_setup,
+                )
+                    .chain(),
             )
             .add_systems(
                 Update,
-                (handle_scene_events,).in_set(PluginSystemSet::InGame),
+                move_to_in_game.run_if(in_state(GameState::SceneLoading)),
             )
+            .add_systems(Update, (handle_scene_events,))
+            // difficulty...
+            .insert_resource(DifficultyResource::default())
+            .add_systems(Update, (update_difficulty,).in_set(PluginSystemSet::InGame))
             // ground...
             .insert_resource(GroundsResource::default())
             .add_systems(
@@ -109,9 +234,30 @@ impl Plugin for KeepItRollingGamePlugin {
             )
             // walls...
             .add_event::<WallEvent>()
+            .insert_resource(WallBudgetResource::default())
+            .add_systems(OnEnter(GameState::SceneLoading), reset_wall_budget)
             .add_systems(
                 Update,
-                (pick_ground_point_raycast, handle_wall_events).in_set(PluginSystemSet::InGame),
+                (
+                    recharge_wall_budget,
+                    handle_wall_events,
+                    handle_bumper_contacts,
+                )
+                    .in_set(PluginSystemSet::InGame),
+            )
+            // replay...
+            .add_systems(
+                Update,
+                playback_wall_events
+                    .before(handle_wall_events)
+                    .in_set(PluginSystemSet::InGame),
+            )
+            .add_systems(
+                Update,
+                // before walls are removed, to look them up...
+                record_wall_events
+                    .before(handle_wall_events)
+                    .in_set(PluginSystemSet::InGame),
             )
             // game over sensor...
             .add_systems(
@@ -122,10 +268,161 @@ impl Plugin for KeepItRollingGamePlugin {
                 )
                     .in_set(PluginSystemSet::InGame),
             )
+            // cleanup
+            .insert_resource(CleanupSettingsResource {
+                disintegrate_grounds: self.disintegration,
+            })
+            .add_systems(First, cleanup)
+            .add_systems(
+                Update,
+                (start_ground_disintegration, fade_disintegrating_grounds),
+            )
+            // debug...
+            .register_type::<GroundsResource>()
+            .register_type::<RunSeed>()
+            .register_type::<PhysicsStepsResource>()
+            .register_type::<GameTuning>()
+            .register_type::<DifficultyResource>()
+            .register_type::<WallBudgetResource>()
+            .register_type::<CleanupSettingsResource>()
+            .register_type::<InputActions>();
+        // menu...
+        if self.auto_start {
+            app.add_systems(OnEnter(GameState::Menu), auto_start_game_on_menu);
+        }
+        // persistence...
+        if self.persistence {
+            app.add_systems(Startup, (load_high_scores, load_settings))
+                .add_systems(Update, (save_high_scores, save_settings))
+                .add_systems(OnEnter(GameState::GameOver), save_replay);
+        }
+    }
+}
+
+/// # Keep It Rolling Input Plugin
+///
+/// Wall placement from the mouse/keyboard, touch and gamepads, picked on the grounds
+/// through the camera. Needs a `PrimaryWindow` and gizmos but no egui, so it can be
+/// driven headless with synthetic input. Part of [`KeepItRollingPresentationPlugin`].
+pub struct KeepItRollingInputPlugin;
+
+impl Plugin for KeepItRollingInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WallPlacementResource::default())
+            .add_systems(
+                Update,
+                (
+                    handle_mouse_wall_input,
+                    handle_touch_wall_input,
+                    handle_gamepad_wall_input,
+                    pick_ground_point_raycast,
+                )
+                    .chain()
+                    .run_if(is_replay_recording)
+                    .before(record_wall_events)
+                    .before(handle_wall_events)
+                    .in_set(PluginSystemSet::InGame),
+            );
+    }
+}
+
+/// # Keep It Rolling Presentation Plugin
+///
+/// Everything that needs a `PrimaryWindow`: materials, egui UI, input picking,
+/// camera/light follow and debug tooling. Requires [`KeepItRollingCorePlugin`].
+pub struct KeepItRollingPresentationPlugin;
+
+impl Plugin for KeepItRollingPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app //
+            // background...
+            .insert_resource(ClearColor(Color::BLACK))
+            // window...
+            .add_systems(Startup, setup_window)
+            // materials
+            .add_plugins(MaterialPlugin::<GlowyMaterial>::default())
+            .add_systems(Startup, setup_glowy_env_texture)
+            .add_systems(Update, update_ball_glow)
+            // particles...
+            .add_plugins(ParticlesPlugin)
+            .add_systems(
+                Update,
+                (add_ball_trails, add_ground_sparks, update_ground_sparks),
+            )
+            .add_systems(OnEnter(GameState::GameOver), spawn_game_over_burst)
+            // audio...
+            .add_audio_source::<Synth>()
+            .add_audio_source::<MusicPattern>()
+            .add_systems(Startup, (setup_sounds, setup_music))
+            .add_systems(
+                Update,
+                (
+                    play_wall_draw_sound,
+                    play_wall_hit_sounds,
+                    play_overheat_warning,
+                )
+                    .in_set(PluginSystemSet::InGame),
+            )
+            .insert_resource(AudioMixerResource::default())
+            .register_type::<AudioMixerResource>()
+            .add_systems(
+                Update,
+                (
+                    update_audio_mixer,
+                    (update_rolling_hum, update_music_layers),
+                )
+                    .chain(),
+            )
+            .add_systems(OnEnter(GameState::GameOver), play_game_over_sting)
+            // egui
+            .add_plugins(EguiPlugin)
+            .add_systems(Startup, init_egui_context)
+            .add_systems(Update, display_audio_unlock_hint)
+            // settings...
+            .add_systems(Update, (display_settings, apply_settings))
+            // menu, pause and game over screens...
+            .add_systems(Update, display_main_menu.run_if(in_state(GameState::Menu)))
+            .add_systems(
+                Update,
+                handle_pause_input
+                    .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused))),
+            )
+            .add_systems(
+                Update,
+                display_pause_menu.run_if(in_state(GameState::Paused)),
+            )
+            .add_systems(
+                Update,
+                display_game_over.run_if(in_state(GameState::GameOver)),
+            )
+            // scoring...
+            .add_systems(
+                Update,
+                (display_scoreboard,).in_set(PluginSystemSet::InGame),
+            )
+            // walls...
+            .add_plugins(KeepItRollingInputPlugin)
             // camera
+            .insert_resource(CameraOrbitResource::default())
+            .register_type::<CameraOrbitResource>()
+            .add_systems(
+                Update,
+                (cycle_camera_mode, move_camera_focus_with_grounds)
+                    .chain()
+                    .in_set(PluginSystemSet::InGame),
+            )
+            .add_systems(Update, apply_camera_projection)
+            .add_systems(OnEnter(GameState::Paused), start_camera_orbit)
+            .add_systems(Update, orbit_camera.run_if(in_state(GameState::Paused)))
+            // camera shake, undone before and reapplied after the camera moves...
+            .add_systems(PreUpdate, restore_camera_shake)
             .add_systems(
                 Update,
-                (move_camera_focus_with_grounds,).in_set(PluginSystemSet::InGame),
+                (add_camera_shake_trauma,).in_set(PluginSystemSet::InGame),
+            )
+            .add_systems(
+                PostUpdate,
+                apply_camera_shake.before(TransformSystem::TransformPropagate),
             )
             // lights
             .add_systems(
@@ -133,15 +430,22 @@ impl Plugin for KeepItRollingGamePlugin {
                 (move_lighting_with_grounds,).in_set(PluginSystemSet::InGame),
             )
             // credits...
-            .add_systems(Update, (display_credits,))
-            // cleanup
-            .add_systems(First, cleanup)
-            // debug...
-            // .add_plugins(WorldInspectorPlugin::default())
-            .register_type::<GroundsResource>()
-            // .add_plugins(ResourceInspectorPlugin::<GroundsResource>::default())
-            // other...
-            .add_systems(Startup, || info!("Game Started..."));
+            .add_systems(Update, (display_credits,));
+        // dev tooling...
+        #[cfg(feature = "dev")]
+        app.add_plugins((
+            // physics debug render, shown with the fly cam
+            RapierDebugRenderPlugin {
+                enabled: false,
+                ..default()
+            },
+            // fly cam (debug/spectator, cycled to with `C`)
+            FlyCameraPlugin,
+            FpsDisplayPlugin,
+        ));
+        // debug (inspectors toggled from the console)...
+        #[cfg(feature = "dev_console")]
+        app.add_plugins(DevConsolePlugin);
     }
 }
 
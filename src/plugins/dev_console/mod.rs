@@ -0,0 +1,4 @@
+mod plugin;
+mod systems;
+
+pub use plugin::DevConsolePlugin;
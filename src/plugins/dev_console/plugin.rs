@@ -0,0 +1,50 @@
+use bevy::{input::InputSystem, prelude::*};
+use bevy_inspector_egui::quick::{ResourceInspectorPlugin, WorldInspectorPlugin};
+
+use crate::resources::GroundsResource;
+
+use super::systems::{
+    consume_typed_keys, display_dev_console, freeze_timers, handle_dump_commands,
+    handle_scene_commands, handle_toggle_commands, toggle_dev_console, DevCommand,
+    DevConsoleResource,
+};
+
+/// # Dev Console Plugin
+///
+/// In-game developer console, toggled with the backquote key (`` ` ``).
+/// Type `help` for its commands. Requires the `EguiPlugin` to be added first.
+pub struct DevConsolePlugin;
+
+impl Plugin for DevConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app // app
+            .init_resource::<DevConsoleResource>()
+            .add_event::<DevCommand>()
+            // inspectors, toggled from the console...
+            .add_plugins(
+                WorldInspectorPlugin::default()
+                    .run_if(|console: Res<DevConsoleResource>| console.world_inspector),
+            )
+            .add_plugins(
+                ResourceInspectorPlugin::<GroundsResource>::default()
+                    .run_if(|console: Res<DevConsoleResource>| console.grounds_inspector),
+            )
+            .add_systems(PreUpdate, consume_typed_keys.after(InputSystem))
+            .add_systems(
+                Update,
+                (
+                    toggle_dev_console,
+                    display_dev_console,
+                    (
+                        handle_toggle_commands,
+                        handle_scene_commands,
+                        handle_dump_commands,
+                    ),
+                    freeze_timers,
+                )
+                    .chain(),
+            )
+            // rest...
+            .add_systems(Startup, || info!("Starting DevConsolePlugin..."));
+    }
+}
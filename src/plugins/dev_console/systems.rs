@@ -0,0 +1,346 @@
+use std::str::FromStr;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    components::{Cleanup, Ground, GroundIndex, GroundLayout, RollingBall},
+    plugins::fly_camera::systems::{cycle_active_camera, CameraTracker},
+    resources::{GroundsResource, ScoresResource},
+    systems::{egui::get_default_egui_frame, ground::next_ground_transform, scene::spawn_ground},
+    tuning::GameTuning,
+};
+
+/// Lines of output kept by the console.
+const LOG_MAX_LINES: usize = 200;
+
+const HELP: &str = "\
+help             list the commands
+clear            clear the console
+inspector        toggle the world inspector
+grounds          toggle the GroundsResource inspector
+debug_render     toggle the Rapier debug render
+fly_cam          switch to the next camera (ie. the fly camera and back)
+spawn [count]    spawn panels after the last one
+velocity x y z   set the ball's velocity
+freeze           freeze/unfreeze the overheat timers and stopwatch
+dump             print the GroundsResource and ScoresResource";
+
+#[derive(Debug, Default, Resource)]
+pub struct DevConsoleResource {
+    pub open: bool,
+    pub input: String,
+    pub log: Vec<String>,
+    pub world_inspector: bool,
+    pub grounds_inspector: bool,
+    /// Keep the overheat timers of grounds and the run stopwatch paused.
+    pub freeze_timers: bool,
+}
+
+impl DevConsoleResource {
+    /// Print a line to the console (and the log).
+    pub fn print(&mut self, line: impl Into<String>) {
+        let line = line.into();
+        info!("{line}");
+        self.log.push(line);
+        let overflow = self.log.len().saturating_sub(LOG_MAX_LINES);
+        self.log.drain(..overflow);
+    }
+}
+
+#[derive(Debug, Clone, Event)]
+pub enum DevCommand {
+    Help,
+    Clear,
+    WorldInspector,
+    GroundsInspector,
+    DebugRender,
+    FlyCamera,
+    SpawnGrounds { count: usize },
+    BallVelocity(Vec3),
+    FreezeTimers,
+    Dump,
+}
+
+impl FromStr for DevCommand {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut args = input.split_whitespace();
+        let Some(name) = args.next() else {
+            return Err("empty command, try `help`".to_string());
+        };
+        let command = match name {
+            "help" => DevCommand::Help,
+            "clear" => DevCommand::Clear,
+            "inspector" => DevCommand::WorldInspector,
+            "grounds" => DevCommand::GroundsInspector,
+            "debug_render" => DevCommand::DebugRender,
+            "fly_cam" => DevCommand::FlyCamera,
+            "spawn" => {
+                let count = args
+                    .next()
+                    .map(str::parse)
+                    .transpose()
+                    .map_err(|_| "usage: spawn [count]")?
+                    .unwrap_or(1);
+                DevCommand::SpawnGrounds { count }
+            }
+            "velocity" => {
+                let values = args
+                    .map(str::parse)
+                    .collect::<Result<Vec<f32>, _>>()
+                    .map_err(|_| "usage: velocity x y z")?;
+                let [x, y, z] = values[..] else {
+                    return Err("usage: velocity x y z".to_string());
+                };
+                DevCommand::BallVelocity(Vec3::new(x, y, z))
+            }
+            "freeze" => DevCommand::FreezeTimers,
+            "dump" => DevCommand::Dump,
+            _ => return Err(format!("unknown command `{name}`, try `help`")),
+        };
+        Ok(command)
+    }
+}
+
+fn on_off(enabled: bool) -> &'static str {
+    if enabled {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+pub fn toggle_dev_console(keys: Res<Input<KeyCode>>, mut console: ResMut<DevConsoleResource>) {
+    if keys.just_pressed(KeyCode::Grave) {
+        console.open = !console.open;
+    }
+}
+
+/// Keep keys typed into the console (or any egui text field) from also reaching
+/// the game through [`Input<KeyCode>`], ie. the [`ActionInput`](crate::input::ActionInput).
+/// Egui reads its own keyboard events, so it still gets them.
+pub fn consume_typed_keys(mut egui_contexts: EguiContexts, mut key_input: ResMut<Input<KeyCode>>) {
+    if !egui_contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+    let typed_keys = key_input
+        .get_pressed()
+        .chain(key_input.get_just_released())
+        // still toggles the console...
+        .filter(|key_code| **key_code != KeyCode::Grave)
+        .copied()
+        .collect::<Vec<_>>();
+    for key_code in typed_keys {
+        key_input.reset(key_code);
+    }
+}
+
+pub fn display_dev_console(
+    mut egui_contexts: EguiContexts,
+    mut console: ResMut<DevConsoleResource>,
+    mut dev_commands: EventWriter<DevCommand>,
+) {
+    if !console.open {
+        return;
+    }
+    let frame = get_default_egui_frame();
+    egui::Window::new("Dev Console")
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .frame(frame)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 0.0))
+        .default_width(480.)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(240.)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for line in console.log.iter() {
+                        ui.monospace(line);
+                    }
+                });
+            ui.separator();
+            let response = ui.text_edit_singleline(&mut console.input);
+            // the toggle key shouldn't end up in the input...
+            console.input.retain(|c| c != '`');
+            if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                let input = std::mem::take(&mut console.input);
+                console.print(format!("> {input}"));
+                match input.parse::<DevCommand>() {
+                    Ok(command) => dev_commands.send(command),
+                    Err(err) => console.print(err),
+                }
+                response.request_focus();
+            }
+        });
+}
+
+/// Handle the commands toggling debug tooling.
+pub fn handle_toggle_commands(
+    mut dev_commands: EventReader<DevCommand>,
+    mut console: ResMut<DevConsoleResource>,
+    mut debug_render_ctx: Option<ResMut<DebugRenderContext>>,
+    mut camera_tracker: Option<ResMut<CameraTracker>>,
+    mut cameras: Query<(Entity, &mut Camera)>,
+) {
+    for command in dev_commands.iter() {
+        match command {
+            DevCommand::Help => console.print(HELP),
+            DevCommand::Clear => console.log.clear(),
+            DevCommand::WorldInspector => {
+                console.world_inspector = !console.world_inspector;
+                let line = format!("world inspector: {}", on_off(console.world_inspector));
+                console.print(line);
+            }
+            DevCommand::GroundsInspector => {
+                console.grounds_inspector = !console.grounds_inspector;
+                let line = format!("grounds inspector: {}", on_off(console.grounds_inspector));
+                console.print(line);
+            }
+            DevCommand::DebugRender => {
+                let Some(debug_render_ctx) = debug_render_ctx.as_deref_mut() else {
+                    console.print("no debug render, add the `RapierDebugRenderPlugin`");
+                    continue;
+                };
+                debug_render_ctx.enabled = !debug_render_ctx.enabled;
+                console.print(format!(
+                    "debug render: {}",
+                    on_off(debug_render_ctx.enabled)
+                ));
+            }
+            DevCommand::FlyCamera => {
+                let Some(camera_tracker) = camera_tracker.as_deref_mut() else {
+                    console.print("no camera tracker, add the `FlyCameraPlugin`");
+                    continue;
+                };
+                cycle_active_camera(camera_tracker, &mut cameras);
+            }
+            DevCommand::FreezeTimers => {
+                console.freeze_timers = !console.freeze_timers;
+                let line = format!("freeze timers: {}", on_off(console.freeze_timers));
+                console.print(line);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Handle the commands changing the scene.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn handle_scene_commands(
+    mut commands: Commands,
+    mut dev_commands: EventReader<DevCommand>,
+    mut console: ResMut<DevConsoleResource>,
+    grounds: Query<(&Transform, &GroundIndex, &GroundLayout), With<Ground>>,
+    mut balls: Query<&mut Velocity, With<RollingBall>>,
+    mut grounds_res: ResMut<GroundsResource>,
+    tuning: Res<GameTuning>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for command in dev_commands.iter() {
+        match command {
+            DevCommand::SpawnGrounds { count } => {
+                let Some((mut transform, GroundIndex(mut index), mut layout)) = grounds
+                    .iter()
+                    .max_by_key(|(_, GroundIndex(index), _)| *index)
+                    .map(|(transform, index, layout)| (*transform, *index, layout.clone()))
+                else {
+                    console.print("no panels to spawn after, start a run first");
+                    continue;
+                };
+                // the panel after the current one becomes its next, as the mid sensor would set
+                let next_index = grounds_res
+                    .current_ground
+                    .and_then(|current_ground| grounds.get(current_ground).ok())
+                    .map(|(_, GroundIndex(current_index), _)| current_index + 1);
+                for _ in 0..*count {
+                    // not from the `RunRng`, so the run's own panels stay the same...
+                    index += 1;
+                    let next_layout = GroundLayout::generate(
+                        &mut rand::thread_rng(),
+                        &tuning,
+                        GroundIndex(index),
+                    );
+                    transform = next_ground_transform(&transform, &layout, &next_layout, &tuning);
+                    let Some(ground_ent) = spawn_ground(
+                        &mut commands,
+                        &mut meshes,
+                        &mut materials,
+                        &tuning,
+                        GroundIndex(index),
+                        next_layout.clone(),
+                    ) else {
+                        break;
+                    };
+                    commands
+                        .entity(ground_ent)
+                        .insert(TransformBundle::from_transform(transform));
+                    if grounds_res.next_ground.is_none() && next_index == Some(index) {
+                        grounds_res.next_ground = Some(ground_ent);
+                    }
+                    layout = next_layout;
+                }
+                console.print(format!("spawned panels up to #{index}"));
+            }
+            DevCommand::BallVelocity(linvel) => {
+                for mut velocity in balls.iter_mut() {
+                    velocity.linvel = *linvel;
+                }
+                console.print(format!("ball velocity: {linvel}"));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Handle the commands printing resources.
+pub fn handle_dump_commands(
+    mut dev_commands: EventReader<DevCommand>,
+    mut console: ResMut<DevConsoleResource>,
+    ground_res: Res<GroundsResource>,
+    scores_res: Res<ScoresResource>,
+) {
+    for command in dev_commands.iter() {
+        if let DevCommand::Dump = command {
+            console.print(format!("{:#?}", *ground_res));
+            console.print(format!("{:#?}", *scores_res));
+        }
+    }
+}
+
+/// Keep (un)pausing the overheat timers and the stopwatch, while/once unfrozen.
+pub fn freeze_timers(
+    console: Res<DevConsoleResource>,
+    mut cleanups: Query<&mut Cleanup>,
+    mut scores_res: ResMut<ScoresResource>,
+) {
+    let freeze = console.freeze_timers;
+    if !freeze && !console.is_changed() {
+        return;
+    }
+    for mut cleanup in cleanups.iter_mut() {
+        // not a change of the kind of cleanup...
+        let (Cleanup::OnTimeout { timer } | Cleanup::Disintegrate { timer }) =
+            cleanup.bypass_change_detection()
+        else {
+            continue;
+        };
+        if freeze {
+            timer.pause();
+        } else {
+            timer.unpause();
+        }
+    }
+    if let Some(stopwatch) = scores_res.bypass_change_detection().stopwatch.as_mut() {
+        if freeze {
+            stopwatch.pause();
+        } else {
+            stopwatch.unpause();
+        }
+    }
+}
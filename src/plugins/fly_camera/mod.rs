@@ -2,3 +2,4 @@ pub mod plugin;
 pub mod systems;
 
 pub use plugin::FlyCameraPlugin;
+pub use systems::TrackableCamera;
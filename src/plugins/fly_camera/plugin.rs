@@ -1,22 +1,31 @@
 use bevy::prelude::*;
 
-use super::systems::{camera_controller, camera_tracker, setup, CameraTracker, FlyCameraSettings};
+use crate::input::InputActions;
+
+use super::systems::{
+    camera_controller, camera_tracker, setup, switch_fly_camera, CameraTracker, FlyCameraSettings,
+};
 
 /// # Fly Camera Plugin
 ///
 /// Fly Camera Plugin adapted from bevy examples.
 ///
+/// The fly camera starts inactive, cameras marked `TrackableCamera` (like the game's)
+/// are cycled through with `C`, the fly camera taking over where the previous one was.
+/// See `FlyCameraSettings` to pause the physics/show the Rapier debug render
+/// (needs the `RapierDebugRenderPlugin`) while flying around.
+///
 /// ```ignore
 ///Controls:
 ///    MOUSE       - Move camera orientation
-///    LClick/M    - Enable mouse movement
+///    LAlt        - Toggle mouse movement
 ///    WSAD        - forward/back/strafe left/right
 ///    LShift      - 'run'
 ///    E           - up
 ///    Q           - down
 ///    L           - animate light direction
 ///    U           - toggle shadows
-///    C           - cycle through cameras
+///    C           - cycle through cameras (rebindable `InputAction::CycleCamera`)
 /// ```
 pub struct FlyCameraPlugin;
 
@@ -25,8 +34,9 @@ impl Plugin for FlyCameraPlugin {
         app // app
             .insert_resource(FlyCameraSettings::default())
             .insert_resource(CameraTracker::default())
+            .init_resource::<InputActions>()
             .add_systems(Startup, setup)
             .add_systems(Update, camera_controller)
-            .add_systems(Update, camera_tracker);
+            .add_systems(Update, (camera_tracker, switch_fly_camera).chain());
     }
 }
@@ -4,12 +4,22 @@ use bevy::prelude::*;
 
 use bevy::window::CursorGrabMode;
 use bevy::{input::mouse::MouseMotion, prelude::*};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    input::{ActionInput, InputAction},
+    state::GameState,
+};
 
 #[derive(Debug, Resource)]
 pub struct FlyCameraSettings {
     pub init_transform: Transform,
     pub init_walk_speed: f32,
     pub init_run_speed: f32,
+    /// Pause the physics while flying around (game timers keep running).
+    pub pause_physics: bool,
+    /// Show the Rapier debug render while flying around.
+    pub debug_render: bool,
 }
 
 impl Default for FlyCameraSettings {
@@ -21,6 +31,8 @@ impl Default for FlyCameraSettings {
             init_transform,
             init_walk_speed: controller.walk_speed,
             init_run_speed: controller.run_speed,
+            pause_physics: false,
+            debug_render: true,
         }
     }
 }
@@ -40,7 +52,8 @@ pub fn setup(mut commands: Commands, settings: Res<FlyCameraSettings>) {
             Camera3dBundle {
                 transform: init_transform,
                 camera: Camera {
-                    is_active: true,
+                    // switched to with the `CameraTracker`...
+                    is_active: false,
                     // hdr compilation fails for non rgb8uorm textures on web/wasm and requires rgb16f
                     // hdr: true,
                     ..default()
@@ -80,6 +93,13 @@ impl CameraTracker {
         self.cameras.push(camera_entity);
     }
 
+    /// Stop tracking a (despawned) camera
+    pub fn untrack_camera(&mut self, camera_entity: Entity) {
+        let active_camera = self.get_active_camera();
+        self.cameras.retain(|c| c != &camera_entity);
+        self.active_index = active_camera.and_then(|e| self.cameras.iter().position(|c| c == &e));
+    }
+
     pub fn get_active_camera(&self) -> Option<Entity> {
         self.active_index.map(|i| self.cameras[i])
     }
@@ -100,12 +120,17 @@ impl CameraTracker {
 
 pub fn camera_tracker(
     mut camera_tracker: ResMut<CameraTracker>,
-    keyboard_input: Res<Input<KeyCode>>,
+    action_input: ActionInput,
     mut queries: ParamSet<(
         Query<(Entity, &mut Camera), (Added<Camera>, With<TrackableCamera>)>,
         Query<(Entity, &mut Camera)>,
     )>,
+    mut removed_cameras: RemovedComponents<TrackableCamera>,
 ) {
+    // forget despawned cameras (eg. the game camera on restart)
+    for entity in removed_cameras.iter() {
+        camera_tracker.untrack_camera(entity);
+    }
     // track all added scene camera entities
     let added_camera_query = queries.p0();
     for (entity, _) in added_camera_query.iter() {
@@ -129,22 +154,77 @@ pub fn camera_tracker(
             }
         }
     }
-    if keyboard_input.just_pressed(KeyCode::C) {
-        // disable currently active camera
-        if let Some(e) = camera_tracker.get_active_camera() {
-            info!("Switching active camera from {:?}", e);
-            if let Ok((_, mut camera)) = queries.p1().get_mut(e) {
-                camera.is_active = false;
-            }
+    if action_input.just_pressed(InputAction::CycleCamera) {
+        cycle_active_camera(&mut camera_tracker, &mut queries.p1());
+    }
+}
+
+/// Switch from the active camera to the next tracked one.
+pub fn cycle_active_camera(
+    camera_tracker: &mut CameraTracker,
+    cameras: &mut Query<(Entity, &mut Camera)>,
+) {
+    // disable currently active camera
+    if let Some(e) = camera_tracker.get_active_camera() {
+        info!("Switching active camera from {:?}", e);
+        if let Ok((_, mut camera)) = cameras.get_mut(e) {
+            camera.is_active = false;
         }
+    }
 
-        // enable next active camera
-        if let Some(e) = camera_tracker.set_next_active() {
-            if let Ok((_, mut camera)) = queries.p1().get_mut(e) {
-                camera.is_active = true;
+    // enable next active camera
+    if let Some(e) = camera_tracker.set_next_active() {
+        if let Ok((_, mut camera)) = cameras.get_mut(e) {
+            camera.is_active = true;
+        }
+    }
+}
+
+/// Start the fly camera where the previous camera was, and pause the physics/show
+/// the debug render (see [`FlyCameraSettings`]) while it is the active one.
+#[allow(clippy::too_many_arguments)]
+pub fn switch_fly_camera(
+    camera_tracker: Res<CameraTracker>,
+    mut transforms: Query<&mut Transform, With<TrackableCamera>>,
+    mut controllers: Query<&mut CameraController>,
+    settings: Res<FlyCameraSettings>,
+    mut rapier_conf: ResMut<RapierConfiguration>,
+    debug_render_ctx: Option<ResMut<DebugRenderContext>>,
+    state: Res<State<GameState>>,
+    mut prev_active: Local<Option<Entity>>,
+    mut paused_physics: Local<bool>,
+) {
+    if !camera_tracker.is_changed() {
+        return;
+    }
+    let active = camera_tracker.get_active_camera();
+    if active == *prev_active {
+        return;
+    }
+    let fly_active = active.is_some_and(|e| controllers.contains(e));
+    if let (true, Some(prev), Some(active)) = (fly_active, *prev_active, active) {
+        if let Ok(prev_transform) = transforms.get(prev).copied() {
+            if let Ok(mut transform) = transforms.get_mut(active) {
+                *transform = prev_transform;
+            }
+            if let Ok(mut controller) = controllers.get_mut(active) {
+                // pick up the yaw/pitch of the new orientation
+                controller.initialized = false;
             }
         }
     }
+    if let Some(mut debug_render_ctx) = debug_render_ctx {
+        debug_render_ctx.enabled = fly_active && settings.debug_render;
+    }
+    if fly_active && settings.pause_physics && rapier_conf.physics_pipeline_active {
+        rapier_conf.physics_pipeline_active = false;
+        *paused_physics = true;
+    } else if !fly_active && *paused_physics {
+        // only resume what was paused here, eg. not the pause menu
+        rapier_conf.physics_pipeline_active = *state.get() == GameState::InGame;
+        *paused_physics = false;
+    }
+    *prev_active = active;
 }
 
 #[derive(Component, Debug)]
@@ -206,7 +286,12 @@ pub fn camera_controller(
     if let Ok((camera, mut transform, mut options)) = query.get_single_mut() {
         let mut window = windows.get_single_mut().unwrap();
         if !camera.is_active {
-            // if not active do not apply transformations
+            // if not active do not apply transformations (and release the cursor)
+            if *move_toggled {
+                *move_toggled = false;
+                window.cursor.grab_mode = CursorGrabMode::None;
+                window.cursor.visible = true;
+            }
             return;
         }
         if !options.initialized {
@@ -2,3 +2,4 @@ mod plugin;
 mod systems;
 
 pub use plugin::FpsDisplayPlugin;
+pub use systems::FpsDisplaySettings;
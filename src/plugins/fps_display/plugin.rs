@@ -1,6 +1,6 @@
 use bevy::{diagnostic::FrameTimeDiagnosticsPlugin, prelude::*};
 
-use super::systems::fps_text_update_system;
+use super::systems::{fps_text_update_system, FpsDisplaySettings};
 
 /// # FPS Display Plugin
 ///
@@ -11,6 +11,7 @@ impl Plugin for FpsDisplayPlugin {
     fn build(&self, app: &mut App) {
         app // app
             .add_plugins(FrameTimeDiagnosticsPlugin::default())
+            .init_resource::<FpsDisplaySettings>()
             .add_systems(Update, fps_text_update_system)
             // rest...
             .add_systems(Startup, || info!("Starting FpsDisplayPlugin..."));
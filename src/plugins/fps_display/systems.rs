@@ -7,7 +7,25 @@ use bevy_egui::{
     EguiContexts,
 };
 
-pub fn fps_text_update_system(diagnostics: Res<DiagnosticsStore>, mut egui_contexts: EguiContexts) {
+#[derive(Debug, Resource)]
+pub struct FpsDisplaySettings {
+    pub visible: bool,
+}
+
+impl Default for FpsDisplaySettings {
+    fn default() -> Self {
+        Self { visible: true }
+    }
+}
+
+pub fn fps_text_update_system(
+    diagnostics: Res<DiagnosticsStore>,
+    settings: Res<FpsDisplaySettings>,
+    mut egui_contexts: EguiContexts,
+) {
+    if !settings.visible {
+        return;
+    }
     if let Some(fps) = diagnostics.get(FrameTimeDiagnosticsPlugin::FPS) {
         let Some(avg_fps) = fps.average() else {
             return;
@@ -1,5 +1,15 @@
+#[cfg(feature = "dev_console")]
+pub mod dev_console;
+#[cfg(feature = "dev")]
 pub mod fly_camera;
+#[cfg(feature = "dev")]
 pub mod fps_display;
+pub mod particles;
 
-pub use fly_camera::FlyCameraPlugin;
-pub use fps_display::FpsDisplayPlugin;
+#[cfg(feature = "dev_console")]
+pub use dev_console::DevConsolePlugin;
+#[cfg(feature = "dev")]
+pub use fly_camera::{FlyCameraPlugin, TrackableCamera};
+#[cfg(feature = "dev")]
+pub use fps_display::{FpsDisplayPlugin, FpsDisplaySettings};
+pub use particles::{ParticleEmitter, ParticlesPlugin};
@@ -0,0 +1,5 @@
+mod plugin;
+mod systems;
+
+pub use plugin::ParticlesPlugin;
+pub use systems::ParticleEmitter;
@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+
+use super::systems::{draw_particles, update_particle_emitters};
+
+/// # Particles Plugin
+///
+/// Lightweight CPU particles, see [`ParticleEmitter`](super::ParticleEmitter).
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app // app
+            .add_systems(Update, (update_particle_emitters, draw_particles).chain())
+            // rest...
+            .add_systems(Startup, || info!("Starting ParticlesPlugin..."));
+    }
+}
@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+
+/// # Particle Emitter
+///
+/// Spawns particles from a box around its [`GlobalTransform`], `rate` per sec
+/// plus any `burst`. Particles are simulated in world space on the CPU and
+/// drawn as gizmo streaks along their velocity.
+#[derive(Debug, Clone, Component)]
+pub struct ParticleEmitter {
+    /// Particles spawned per sec.
+    pub rate: f32,
+    /// Particles spawned at once on the next update.
+    pub burst: usize,
+    pub max_particles: usize,
+    pub lifetime_secs: f32,
+    /// Local center of the spawn box.
+    pub spawn_offset: Vec3,
+    /// Local half extents of the spawn box.
+    pub spawn_extents: Vec3,
+    /// Initial velocity (in world space).
+    pub velocity: Vec3,
+    /// Max random variation of the initial velocity, along each axis.
+    pub velocity_spread: f32,
+    pub acceleration: Vec3,
+    /// Color at the start and end of a particle's life.
+    pub colors: (Color, Color),
+    /// Length of a particle's streak at the start of its life, shrinks to `0` by the end.
+    pub size: f32,
+    /// Despawn the emitter once it has nothing left to spawn or simulate.
+    pub despawn_when_done: bool,
+    pub particles: Vec<Particle>,
+    /// Fraction of a particle carried over to the next update.
+    pub spawn_accumulator: f32,
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            rate: 0.,
+            burst: 0,
+            max_particles: 256,
+            lifetime_secs: 1.,
+            spawn_offset: Vec3::ZERO,
+            spawn_extents: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+            velocity_spread: 0.,
+            acceleration: Vec3::ZERO,
+            colors: (Color::WHITE, Color::NONE),
+            size: 0.1,
+            despawn_when_done: false,
+            particles: vec![],
+            spawn_accumulator: 0.,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Particle {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub age_secs: f32,
+}
+
+/// Random value from `-1` to `1`, particles are cosmetic so they don't use the `RunRng`.
+fn random_signed() -> f32 {
+    rand::random::<f32>() * 2. - 1.
+}
+
+fn random_signed_vec3() -> Vec3 {
+    Vec3::new(random_signed(), random_signed(), random_signed())
+}
+
+/// Spawn, age and move the particles of every [`ParticleEmitter`].
+pub fn update_particle_emitters(
+    mut commands: Commands,
+    mut emitters: Query<(Entity, &mut ParticleEmitter, &GlobalTransform)>,
+    time: Res<Time>,
+) {
+    let delta_secs = time.delta_seconds();
+    for (emitter_ent, mut emitter, global_transform) in emitters.iter_mut() {
+        let emitter = emitter.as_mut();
+        // simulate...
+        let lifetime_secs = emitter.lifetime_secs;
+        emitter.particles.retain_mut(|particle| {
+            particle.age_secs += delta_secs;
+            particle.velocity += emitter.acceleration * delta_secs;
+            particle.position += particle.velocity * delta_secs;
+            particle.age_secs < lifetime_secs
+        });
+        // spawn...
+        emitter.spawn_accumulator += emitter.rate * delta_secs;
+        let to_spawn = emitter.spawn_accumulator as usize + emitter.burst;
+        emitter.spawn_accumulator = emitter.spawn_accumulator.fract();
+        emitter.burst = 0;
+        let to_spawn = to_spawn.min(
+            emitter
+                .max_particles
+                .saturating_sub(emitter.particles.len()),
+        );
+        for _ in 0..to_spawn {
+            let local_position =
+                emitter.spawn_offset + emitter.spawn_extents * random_signed_vec3();
+            emitter.particles.push(Particle {
+                position: global_transform.transform_point(local_position),
+                velocity: emitter.velocity + random_signed_vec3() * emitter.velocity_spread,
+                age_secs: 0.,
+            });
+        }
+        if emitter.despawn_when_done && emitter.rate <= 0. && emitter.particles.is_empty() {
+            commands.entity(emitter_ent).despawn_recursive();
+        }
+    }
+}
+
+/// Draw the particles as streaks along their velocity, colored/sized over their life.
+pub fn draw_particles(emitters: Query<&ParticleEmitter>, mut gizmos: Gizmos) {
+    for emitter in emitters.iter() {
+        let (color_start, color_end) = emitter.colors;
+        let (color_start, color_end) = (color_start.as_rgba_f32(), color_end.as_rgba_f32());
+        for particle in emitter.particles.iter() {
+            let life = (particle.age_secs / emitter.lifetime_secs).clamp(0., 1.);
+            let color = Vec4::from(color_start).lerp(Vec4::from(color_end), life);
+            let direction = particle.velocity.try_normalize().unwrap_or(Vec3::Y);
+            let length = emitter.size * (1. - life);
+            gizmos.line(
+                particle.position,
+                particle.position - direction * length,
+                Color::rgba(color.x, color.y, color.z, color.w),
+            );
+        }
+    }
+}
@@ -1,7 +1,15 @@
 use bevy::{prelude::*, time::Stopwatch};
+#[cfg(feature = "dev")]
 use bevy_inspector_egui::InspectorOptions;
+use bevy_rapier3d::prelude::TimestepMode;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Resource, Default, Debug, Reflect, InspectorOptions)]
+use crate::{components::WallKind, constants::HIGH_SCORES_MAX_ENTRIES, tuning::GameTuning};
+
+#[derive(Clone, Resource, Default, Debug, Reflect)]
+#[cfg_attr(feature = "dev", derive(InspectorOptions))]
 #[reflect(Resource)]
 pub struct GroundsResource {
     pub previous_ground: Option<Entity>,
@@ -9,27 +17,479 @@ pub struct GroundsResource {
     pub next_ground: Option<Entity>,
 }
 
-#[derive(Clone, Resource, Default, Debug, Reflect, InspectorOptions)]
+impl GroundsResource {
+    /// Whether `ground` is the current or next one, the grounds walls are drawn on.
+    pub fn is_current_or_next(&self, ground: Entity) -> bool {
+        self.current_ground == Some(ground) || self.next_ground == Some(ground)
+    }
+}
+
+#[derive(Clone, Resource, Default, Debug, Reflect)]
+#[cfg_attr(feature = "dev", derive(InspectorOptions))]
 #[reflect(Resource)]
 pub struct ScoresResource {
     pub stopwatch: Option<Stopwatch>,
     pub grounds_passed: u64,
+    /// The finished run beat the high scores, set on game over before it is recorded.
+    pub was_new_best: bool,
+}
+
+/// Current difficulty of the run, derived from the [`ScoresResource`]
+/// and the [`DifficultyCurve`](crate::tuning::DifficultyCurve).
+#[derive(Clone, Resource, Debug, Reflect)]
+#[cfg_attr(feature = "dev", derive(InspectorOptions))]
+#[reflect(Resource)]
+pub struct DifficultyResource {
+    /// From `0` (start of a run) to `1` (max difficulty).
+    pub level: f32,
+    pub overheat_duration_secs: f32,
+    /// Multiplier on the ground angle of newly spawned grounds.
+    pub ground_angle_scale: f32,
+    /// Multiplier on the ground width of newly spawned grounds.
+    pub ground_width_scale: f32,
+    /// Max walls standing on the current and next ground at once.
+    pub max_walls: usize,
+}
+
+impl DifficultyResource {
+    pub fn new(tuning: &GameTuning, level: f32) -> Self {
+        let curve = &tuning.difficulty;
+        let lerp = |scale: f32| 1. + (scale - 1.) * level;
+        let (max_walls_start, max_walls_end) = curve.max_walls;
+        Self {
+            level,
+            overheat_duration_secs: tuning.ground_overheat_duration_secs
+                * lerp(curve.overheat_duration_scale),
+            ground_angle_scale: lerp(curve.ground_angle_scale),
+            ground_width_scale: lerp(curve.ground_width_scale),
+            max_walls: (max_walls_start as f32
+                + (max_walls_end as f32 - max_walls_start as f32) * level)
+                .round() as usize,
+        }
+    }
+}
+
+impl Default for DifficultyResource {
+    fn default() -> Self {
+        Self::new(&GameTuning::default(), 0.)
+    }
+}
+
+/// Pointer and wall placement state, fed by the mouse/keyboard, touch and gamepad input.
+#[derive(Clone, Resource, Default, Debug, Reflect)]
+#[cfg_attr(feature = "dev", derive(InspectorOptions))]
+#[reflect(Resource)]
+pub struct WallPlacementResource {
+    /// Where the player is pointing, in logical window coordinates.
+    pub pointer: Option<Vec2>,
+    /// Whether the pointer is a virtual cursor (moved by a gamepad), to be drawn in game.
+    pub virtual_cursor: bool,
+    /// Angle of the wall around the ground normal.
+    pub angle: f32,
+    pub kind: WallKind,
+    /// Draw the hovered wall this frame.
+    pub place: bool,
+    /// Remove the pointed at wall this frame.
+    pub remove: bool,
+}
+
+/// How entities marked for [`Cleanup`](crate::components::Cleanup) go away.
+#[derive(Clone, Resource, Debug, Reflect)]
+#[cfg_attr(feature = "dev", derive(InspectorOptions))]
+#[reflect(Resource)]
+pub struct CleanupSettingsResource {
+    /// Break overheated grounds apart before despawning them,
+    /// instead of despawning them right away.
+    pub disintegrate_grounds: bool,
+}
+
+impl Default for CleanupSettingsResource {
+    fn default() -> Self {
+        Self {
+            disintegrate_grounds: true,
+        }
+    }
+}
+
+/// Wall inventory, drawing a wall spends from it and removing one refunds.
+#[derive(Clone, Resource, Default, Debug, Reflect)]
+#[cfg_attr(feature = "dev", derive(InspectorOptions))]
+#[reflect(Resource)]
+pub struct WallBudgetResource {
+    pub charges: f32,
+    /// [`ScoresResource::grounds_passed`] already recharged for.
+    pub grounds_passed_recharged: u64,
+}
+
+/// A finished run, as saved in the [`HighScoresResource`].
+#[derive(Clone, Debug, Reflect, Serialize, Deserialize)]
+pub struct HighScore {
+    pub time_survived_secs: f32,
+    pub grounds_passed: u64,
+    /// When the run ended, in seconds since the unix epoch.
+    pub timestamp_secs: u64,
+    pub seed: Option<u64>,
+    /// Settings the run was played with.
+    #[serde(default)]
+    pub settings: HighScoreSettings,
+}
+
+/// The gameplay relevant subset of the [`SettingsResource`] a run was played with.
+#[derive(Clone, Debug, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HighScoreSettings {
+    pub wall_rotation_sensitivity: f32,
+    pub show_ghost_preview: bool,
+}
+
+impl Default for HighScoreSettings {
+    fn default() -> Self {
+        (&SettingsResource::default()).into()
+    }
+}
+
+impl From<&SettingsResource> for HighScoreSettings {
+    fn from(settings: &SettingsResource) -> Self {
+        Self {
+            wall_rotation_sensitivity: settings.wall_rotation_sensitivity,
+            show_ghost_preview: settings.show_ghost_preview,
+        }
+    }
 }
 
-#[derive(Clone, Resource, Default, Debug, Reflect, InspectorOptions)]
+/// Persistent table of all finished runs.
+#[derive(Clone, Resource, Default, Debug, Reflect, Serialize, Deserialize)]
+#[cfg_attr(feature = "dev", derive(InspectorOptions))]
 #[reflect(Resource)]
-pub struct PreviousScoresRes(pub Vec<ScoresResource>);
+pub struct HighScoresResource {
+    pub entries: Vec<HighScore>,
+}
+
+impl HighScoresResource {
+    /// Add a finished run, keeping the table sorted best first
+    /// and capped to [`HIGH_SCORES_MAX_ENTRIES`].
+    pub fn record(&mut self, entry: HighScore) {
+        self.entries.push(entry);
+        self.entries.sort_by(|a, b| {
+            b.grounds_passed
+                .cmp(&a.grounds_passed)
+                .then(b.time_survived_secs.total_cmp(&a.time_survived_secs))
+        });
+        self.entries.truncate(HIGH_SCORES_MAX_ENTRIES);
+    }
+
+    /// Longest time survived in any run.
+    pub fn best_time_survived_secs(&self) -> Option<f32> {
+        self.entries
+            .iter()
+            .map(|entry| entry.time_survived_secs)
+            .max_by(f32::total_cmp)
+    }
+
+    /// Most panels passed in any run.
+    pub fn best_grounds_passed(&self) -> Option<u64> {
+        self.entries.iter().map(|entry| entry.grounds_passed).max()
+    }
 
-#[derive(Clone, Resource, Debug, Reflect, InspectorOptions)]
+    /// Whether a run beats the recorded bests (ties don't count).
+    pub fn is_new_best(&self, grounds_passed: u64, time_survived_secs: f32) -> bool {
+        self.best_grounds_passed()
+            .is_some_and(|best| grounds_passed > best)
+            || self
+                .best_time_survived_secs()
+                .is_some_and(|best| time_survived_secs > best)
+    }
+}
+
+#[derive(Clone, PartialEq, Resource, Debug, Reflect, Serialize, Deserialize)]
+#[cfg_attr(feature = "dev", derive(InspectorOptions))]
 #[reflect(Resource)]
+#[serde(default)]
 pub struct SettingsResource {
     pub wall_rotation_sensitivity: f32,
+    // graphics...
+    pub shadows: bool,
+    pub anti_aliasing: AntiAliasing,
+    pub vsync: bool,
+    /// Multiplier on the pixel density rendered at (eg. of the web canvas).
+    pub resolution_scale: f32,
+    // gameplay...
+    /// Show the translucent preview of the wall being placed.
+    pub show_ghost_preview: bool,
+    /// How fast the camera catches up with the ball.
+    pub camera_follow_stiffness: f32,
+    pub camera_mode: CameraMode,
+    /// Shake the camera on impacts of the ball.
+    pub camera_shake: bool,
+    /// Multiplier on the camera shake.
+    pub camera_shake_strength: f32,
+    // audio...
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub muted: bool,
+    /// Silence the game while its window is not focused.
+    pub mute_on_focus_loss: bool,
+    // hud...
+    pub show_fps: bool,
+    pub show_scoreboard: bool,
 }
 
 impl Default for SettingsResource {
     fn default() -> Self {
         Self {
             wall_rotation_sensitivity: 0.05,
+            shadows: true,
+            anti_aliasing: AntiAliasing::default(),
+            vsync: true,
+            resolution_scale: 1.,
+            show_ghost_preview: true,
+            camera_follow_stiffness: 2.,
+            camera_mode: CameraMode::default(),
+            camera_shake: true,
+            camera_shake_strength: 1.,
+            master_volume: 0.8,
+            music_volume: 0.6,
+            sfx_volume: 1.,
+            muted: false,
+            mute_on_focus_loss: true,
+            show_fps: true,
+            show_scoreboard: true,
+        }
+    }
+}
+
+/// Anti-aliasing method, MSAA is limited to 4 samples for WebGL2.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub enum AntiAliasing {
+    Off,
+    Fxaa,
+    #[default]
+    Msaa4,
+}
+
+/// How the camera follows the ball while in game.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub enum CameraMode {
+    /// Fixed offset from the ball.
+    #[default]
+    Follow,
+    /// Behind the ball, looking along its velocity.
+    Chase,
+    /// Orthographic, straight down on the ball.
+    TopDown,
+}
+
+impl CameraMode {
+    pub const ALL: [CameraMode; 3] = [CameraMode::Follow, CameraMode::Chase, CameraMode::TopDown];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CameraMode::Follow => "Follow",
+            CameraMode::Chase => "Chase",
+            CameraMode::TopDown => "Top-down",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|mode| *mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// Camera orbiting around the ball while paused.
+#[derive(Clone, Resource, Default, Debug, Reflect)]
+#[cfg_attr(feature = "dev", derive(InspectorOptions))]
+#[reflect(Resource)]
+pub struct CameraOrbitResource {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+}
+
+/// Effective volume of each channel, from the [`SettingsResource`] volumes
+/// with any muting applied.
+#[derive(Clone, Resource, Debug, PartialEq, Reflect)]
+#[cfg_attr(feature = "dev", derive(InspectorOptions))]
+#[reflect(Resource)]
+pub struct AudioMixerResource {
+    pub music: f32,
+    pub sfx: f32,
+    /// Audio output was unlocked by a user interaction, browsers block
+    /// playback until then so the wasm build starts locked.
+    pub unlocked: bool,
+}
+
+impl Default for AudioMixerResource {
+    fn default() -> Self {
+        Self {
+            music: 0.,
+            sfx: 0.,
+            unlocked: !cfg!(target_arch = "wasm32"),
+        }
+    }
+}
+
+/// Time spent loading the scene, physics start once it finishes.
+#[derive(Clone, Resource, Default, Debug)]
+pub struct SceneLoadingTimer(pub Timer);
+
+/// Seed of the current run, drives the [`RunRng`].
+#[derive(Clone, Resource, Debug, Reflect)]
+#[cfg_attr(feature = "dev", derive(InspectorOptions))]
+#[reflect(Resource)]
+pub struct RunSeed {
+    pub seed: u64,
+    /// Pick a new random seed for every run.
+    pub reroll: bool,
+}
+
+impl Default for RunSeed {
+    fn default() -> Self {
+        Self {
+            seed: rand::random(),
+            reroll: true,
+        }
+    }
+}
+
+/// Seeded RNG for anything random in a run, reseeded from [`RunSeed`] when a scene loads.
+#[derive(Clone, Resource, Debug)]
+pub struct RunRng(pub ChaCha8Rng);
+
+impl Default for RunRng {
+    fn default() -> Self {
+        Self(ChaCha8Rng::seed_from_u64(0))
+    }
+}
+
+/// A recorded [`WallEvent::Draw`](crate::events::WallEvent::Draw).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReplayWallDraw {
+    /// Physics steps into the run the wall was drawn after.
+    #[serde(alias = "frame")]
+    pub step: u64,
+    /// [`GroundIndex`](crate::components::GroundIndex) of the ground the wall was drawn on.
+    pub ground_index: usize,
+    /// Local transform of the wall relative to its ground.
+    pub transform: Transform,
+    #[serde(default)]
+    pub kind: WallKind,
+}
+
+/// A recorded [`WallEvent::Remove`](crate::events::WallEvent::Remove).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReplayWallRemoval {
+    /// Physics steps into the run the wall was removed after.
+    #[serde(alias = "frame")]
+    pub step: u64,
+    /// [`GroundIndex`](crate::components::GroundIndex) of the ground the wall was on.
+    pub ground_index: usize,
+    /// Local translation of the removed wall, used to find it on playback.
+    pub translation: Vec3,
+}
+
+/// Recorded inputs of a run, enough to replay it given the fixed physics timestep.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub wall_draws: Vec<ReplayWallDraw>,
+    #[serde(default)]
+    pub wall_removals: Vec<ReplayWallRemoval>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReplayMode {
+    /// Record the player's inputs.
+    #[default]
+    Record,
+    /// Feed a recorded replay back instead of the player's inputs.
+    Playback,
+}
+
+#[derive(Clone, Resource, Default, Debug)]
+pub struct ReplayResource {
+    pub mode: ReplayMode,
+    pub replay: Replay,
+    /// Timestep configured for recording, restored after playing back
+    /// (which steps once per frame).
+    pub record_timestep_mode: Option<TimestepMode>,
+}
+
+/// Physics steps simulated in the current run.
+///
+/// Replays are keyed to these rather than to frames, as an interpolated
+/// timestep simulates any number of steps per frame.
+#[derive(Clone, Resource, Default, Debug, Reflect)]
+#[cfg_attr(feature = "dev", derive(InspectorOptions))]
+#[reflect(Resource)]
+pub struct PhysicsStepsResource {
+    pub steps: u64,
+    /// Steps simulated last frame.
+    pub frame_steps: u32,
+    /// Simulated time of the steps of last frame, in seconds.
+    pub frame_secs: f32,
+}
+
+impl ReplayResource {
+    /// Start playing back `replay` from the next run.
+    pub fn play(&mut self, replay: Replay) {
+        self.mode = ReplayMode::Playback;
+        self.replay = replay;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn high_score(grounds_passed: u64, time_survived_secs: f32) -> HighScore {
+        HighScore {
+            time_survived_secs,
+            grounds_passed,
+            timestamp_secs: 0,
+            seed: None,
+            settings: default(),
         }
     }
+
+    #[test]
+    fn records_high_scores_sorted_and_capped() {
+        let runs: Vec<(u64, f32)> = (0..HIGH_SCORES_MAX_ENTRIES as u64 + 10)
+            .map(|run| (run * 3 % 7, (run * 37 % 60) as f32 + 0.5))
+            .collect();
+        let mut high_scores_res = HighScoresResource::default();
+        for (grounds_passed, time_survived_secs) in runs.iter().copied() {
+            high_scores_res.record(high_score(grounds_passed, time_survived_secs));
+        }
+        // most panels first, then longest time...
+        let mut expected = runs;
+        expected.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.total_cmp(&a.1)));
+        expected.truncate(HIGH_SCORES_MAX_ENTRIES);
+        let recorded: Vec<(u64, f32)> = high_scores_res
+            .entries
+            .iter()
+            .map(|entry| (entry.grounds_passed, entry.time_survived_secs))
+            .collect();
+        assert_eq!(recorded.len(), HIGH_SCORES_MAX_ENTRIES);
+        assert_eq!(recorded, expected);
+        assert_eq!(high_scores_res.best_grounds_passed(), Some(6));
+        assert_eq!(
+            high_scores_res.best_time_survived_secs(),
+            expected
+                .iter()
+                .map(|(_, time)| *time)
+                .max_by(f32::total_cmp)
+        );
+    }
+
+    #[test]
+    fn ties_are_not_new_bests() {
+        let mut high_scores_res = HighScoresResource::default();
+        assert!(!high_scores_res.is_new_best(3, 10.));
+        high_scores_res.record(high_score(3, 10.));
+        assert!(!high_scores_res.is_new_best(3, 10.));
+        assert!(high_scores_res.is_new_best(4, 5.));
+        assert!(high_scores_res.is_new_best(1, 10.5));
+    }
 }
@@ -6,4 +6,6 @@ pub enum GameState {
     Menu,
     SceneLoading,
     InGame,
+    Paused,
+    GameOver,
 }
@@ -0,0 +1,263 @@
+use bevy::{
+    audio::{AudioSinkPlayback, Volume},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use bevy_egui::{egui, EguiContexts};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    audio::synth::{SoundEffects, Synth, Waveform},
+    components::{Cleanup, Ground, Hazard, RollingBall, RollingHum, Wall},
+    input::{ActionInput, InputAction},
+    resources::{AudioMixerResource, GroundsResource, SettingsResource},
+    state::GameState,
+};
+
+use super::{egui::get_default_egui_frame, ground::current_overheat_urgency};
+
+/// Impact speed (along the contact normal) a wall hit plays at full volume.
+const WALL_HIT_FULL_VOLUME_SPEED: f32 = 10.;
+/// Ball speed the rolling hum plays at full volume.
+const ROLLING_HUM_FULL_VOLUME_SPEED: f32 = 15.;
+const ROLLING_HUM_VOLUME: f32 = 0.3;
+
+/// Synthesize the sound effects and start the (paused) rolling hum.
+pub fn setup_sounds(mut commands: Commands, mut synths: ResMut<Assets<Synth>>) {
+    let sound_effects = SoundEffects {
+        wall_draw: synths.add(Synth {
+            waveform: Waveform::Square,
+            frequency: 660.,
+            frequency_end: 990.,
+            duration_secs: Some(0.08),
+            ..default()
+        }),
+        wall_hit: synths.add(Synth {
+            waveform: Waveform::Noise,
+            duration_secs: Some(0.12),
+            release_secs: 0.1,
+            ..default()
+        }),
+        overheat_beep: synths.add(Synth {
+            waveform: Waveform::Square,
+            frequency: 880.,
+            frequency_end: 880.,
+            duration_secs: Some(0.06),
+            release_secs: 0.02,
+            ..default()
+        }),
+        game_over: synths.add(Synth {
+            waveform: Waveform::Saw,
+            frequency: 440.,
+            frequency_end: 110.,
+            duration_secs: Some(0.9),
+            release_secs: 0.4,
+            ..default()
+        }),
+        rolling_hum: synths.add(Synth {
+            frequency: 90.,
+            frequency_end: 90.,
+            duration_secs: None,
+            attack_secs: 0.,
+            ..default()
+        }),
+    };
+    commands.spawn((
+        AudioSourceBundle {
+            source: sound_effects.rolling_hum.clone(),
+            settings: PlaybackSettings::LOOP
+                .with_volume(Volume::new_relative(0.))
+                .paused(),
+        },
+        RollingHum,
+    ));
+    commands.insert_resource(sound_effects);
+}
+
+fn play_sound(
+    commands: &mut Commands,
+    mixer_res: &AudioMixerResource,
+    sound: &Handle<Synth>,
+    volume: f32,
+    speed: f32,
+) {
+    let volume = volume * mixer_res.sfx;
+    // don't queue up sounds that can't be heard (eg. before audio is unlocked)...
+    if volume <= 0. {
+        return;
+    }
+    commands.spawn(AudioSourceBundle {
+        source: sound.clone(),
+        settings: PlaybackSettings::DESPAWN
+            .with_volume(Volume::new_relative(volume))
+            .with_speed(speed),
+    });
+}
+
+pub fn play_wall_draw_sound(
+    mut commands: Commands,
+    walls: Query<(), Added<Wall>>,
+    sound_effects: Res<SoundEffects>,
+    mixer_res: Res<AudioMixerResource>,
+) {
+    for _ in walls.iter() {
+        play_sound(&mut commands, &mixer_res, &sound_effects.wall_draw, 0.4, 1.);
+    }
+}
+
+/// Thud on the ball hitting a wall (or hazard), as loud as the impact.
+#[allow(clippy::type_complexity)]
+pub fn play_wall_hit_sounds(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    balls: Query<&Velocity, With<RollingBall>>,
+    walls: Query<(), Or<(With<Wall>, With<Hazard>)>>,
+    rapier_context: Res<RapierContext>,
+    sound_effects: Res<SoundEffects>,
+    mixer_res: Res<AudioMixerResource>,
+) {
+    for event in collision_events.iter() {
+        let CollisionEvent::Started(ent_a, ent_b, _) = event else {
+            continue;
+        };
+        let (ball_ent, wall_ent) = if balls.contains(*ent_a) {
+            (*ent_a, *ent_b)
+        } else {
+            (*ent_b, *ent_a)
+        };
+        let (Ok(ball_vel), true) = (balls.get(ball_ent), walls.contains(wall_ent)) else {
+            continue;
+        };
+        // impact speed along the contact normal, if known...
+        let impact_speed = rapier_context
+            .contact_pair(ball_ent, wall_ent)
+            .and_then(|contact_pair| {
+                let manifold = contact_pair.manifolds().next()?;
+                Some(ball_vel.linvel.dot(manifold.normal()).abs())
+            })
+            .unwrap_or(ball_vel.linvel.length());
+        let volume = (impact_speed / WALL_HIT_FULL_VOLUME_SPEED).clamp(0.05, 1.);
+        play_sound(
+            &mut commands,
+            &mixer_res,
+            &sound_effects.wall_hit,
+            volume,
+            1.,
+        );
+    }
+}
+
+/// Beep faster and higher as the overheat timer of the current ground runs out.
+pub fn play_overheat_warning(
+    mut commands: Commands,
+    grounds: Query<&Cleanup, With<Ground>>,
+    ground_res: Res<GroundsResource>,
+    sound_effects: Res<SoundEffects>,
+    mixer_res: Res<AudioMixerResource>,
+    time: Res<Time>,
+    mut secs_since_beep: Local<f32>,
+) {
+    *secs_since_beep += time.delta_seconds();
+    let urgency = current_overheat_urgency(&grounds, &ground_res);
+    if urgency <= 0. {
+        return;
+    }
+    let beep_interval_secs = 0.6 - 0.5 * urgency;
+    if *secs_since_beep < beep_interval_secs {
+        return;
+    }
+    *secs_since_beep = 0.;
+    play_sound(
+        &mut commands,
+        &mixer_res,
+        &sound_effects.overheat_beep,
+        0.25,
+        1. + urgency * 0.5,
+    );
+}
+
+pub fn play_game_over_sting(
+    mut commands: Commands,
+    sound_effects: Res<SoundEffects>,
+    mixer_res: Res<AudioMixerResource>,
+) {
+    play_sound(&mut commands, &mixer_res, &sound_effects.game_over, 0.5, 1.);
+}
+
+/// Rolling hum following the ball's speed (pitch and volume), only while in game.
+pub fn update_rolling_hum(
+    hums: Query<&AudioSink, With<RollingHum>>,
+    balls: Query<&Velocity, With<RollingBall>>,
+    state: Res<State<GameState>>,
+    mixer_res: Res<AudioMixerResource>,
+) {
+    let Ok(hum_sink) = hums.get_single() else {
+        return;
+    };
+    let (GameState::InGame, Ok(ball_vel)) = (state.get(), balls.get_single()) else {
+        hum_sink.pause();
+        return;
+    };
+    let speed = ball_vel.linvel.length();
+    hum_sink.set_speed(0.5 + (speed * 0.05).min(1.5));
+    hum_sink.set_volume(
+        (speed / ROLLING_HUM_FULL_VOLUME_SPEED).min(1.) * ROLLING_HUM_VOLUME * mixer_res.sfx,
+    );
+    if hum_sink.is_paused() {
+        hum_sink.play();
+    }
+}
+
+/// Mix the volumes from the [`SettingsResource`], muting on the mute key,
+/// on losing window focus and until audio is unlocked.
+pub fn update_audio_mixer(
+    mut mixer_res: ResMut<AudioMixerResource>,
+    mut settings_res: ResMut<SettingsResource>,
+    action_input: ActionInput,
+    key_input: Res<Input<KeyCode>>,
+    mouse_btn_input: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    if action_input.just_pressed(InputAction::ToggleMute) {
+        settings_res.muted = !settings_res.muted;
+    }
+    // browsers only allow playback after the first user interaction...
+    let unlocked = mixer_res.unlocked
+        || key_input.get_just_pressed().next().is_some()
+        || mouse_btn_input.get_just_pressed().next().is_some()
+        || touches.iter_just_pressed().next().is_some();
+    let focused = windows.get_single().map_or(true, |window| window.focused);
+    let muted = settings_res.muted || (settings_res.mute_on_focus_loss && !focused) || !unlocked;
+    let master_volume = if muted {
+        0.
+    } else {
+        settings_res.master_volume
+    };
+    mixer_res.set_if_neq(AudioMixerResource {
+        music: master_volume * settings_res.music_volume,
+        sfx: master_volume * settings_res.sfx_volume,
+        unlocked,
+    });
+}
+
+/// Hint to interact with the page, shown until audio is unlocked (in browser).
+pub fn display_audio_unlock_hint(
+    mixer_res: Res<AudioMixerResource>,
+    mut egui_contexts: EguiContexts,
+) {
+    if mixer_res.unlocked {
+        return;
+    }
+    let frame = get_default_egui_frame();
+    egui::Window::new("Audio Unlock Hint")
+        .title_bar(false)
+        .collapsible(false)
+        .movable(false)
+        .resizable(false)
+        .frame(frame)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 0.0))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label("Click or press any key to enable sound");
+        });
+}
@@ -1,18 +1,45 @@
-use bevy::prelude::*;
+use bevy::{
+    input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel},
+    prelude::*,
+    render::camera::ScalingMode,
+};
+use bevy_egui::EguiContexts;
 use bevy_rapier3d::prelude::*;
 
 use crate::{
-    components::{Ground, MyCamera, RollingBall},
-    resources::GroundsResource,
+    components::{CameraShake, Ground, MyCamera, RollingBall},
+    constants::{CAMERA_SHAKE_FORCE_THRESHOLD, CAMERA_SHAKE_FULL_FORCE},
+    input::{ActionInput, InputAction},
+    resources::{CameraMode, CameraOrbitResource, GroundsResource, SettingsResource},
 };
 
+/// Distance behind (and above) the ball of the chase camera.
+const CHASE_DISTANCE: f32 = 8.;
+const CHASE_HEIGHT: f32 = 4.;
+/// Distance ahead of the ball the chase camera looks at.
+const CHASE_LOOK_AHEAD: f32 = 4.;
+const TOP_DOWN_HEIGHT: f32 = 30.;
+/// Height of the world the top-down camera shows.
+const TOP_DOWN_VIEW_HEIGHT: f32 = 20.;
+/// Radians of orbit per pixel dragged.
+const ORBIT_SENSITIVITY: f32 = 0.005;
+const ORBIT_DISTANCE_RANGE: (f32, f32) = (3., 40.);
+/// Trauma lost per second.
+const SHAKE_TRAUMA_DECAY: f32 = 1.5;
+/// Offset/roll of the camera at full trauma.
+const SHAKE_MAX_OFFSET: f32 = 0.4;
+const SHAKE_MAX_ROLL: f32 = 0.04;
+const SHAKE_FREQUENCY: f32 = 20.;
+
 #[allow(clippy::type_complexity)]
 pub fn move_camera_focus_with_grounds(
     mut query_cams: Query<(&mut Transform, &MyCamera)>,
     query_grounds: Query<&Transform, (With<Ground>, Without<MyCamera>)>,
     query_ball: Query<(&Velocity, &Transform), (With<RollingBall>, Without<MyCamera>)>,
     ground_res: Res<GroundsResource>,
+    settings_res: Res<SettingsResource>,
     time: Res<Time>,
+    mut chase_direction: Local<Option<Vec3>>,
 ) {
     let Some(current_ground) = ground_res.current_ground else {
         return;
@@ -26,9 +53,196 @@ pub fn move_camera_focus_with_grounds(
     let Ok((ball_vel, ball_transform)) = query_ball.get_single() else {
         return;
     };
-    let cam_transform_lerp_fact = 2.;
-    cam_transform.translation = cam_transform.translation.lerp(
-        ball_transform.translation + init_transform.translation,
-        time.delta_seconds() * cam_transform_lerp_fact,
-    );
+    let cam_transform_lerp_fact = settings_res.camera_follow_stiffness;
+    let lerp_fact = (time.delta_seconds() * cam_transform_lerp_fact).min(1.);
+    let ball_translation = ball_transform.translation;
+    let target_transform = match settings_res.camera_mode {
+        CameraMode::Follow => Transform {
+            translation: ball_translation + init_transform.translation,
+            ..*init_transform
+        },
+        CameraMode::Chase => {
+            // smoothed (horizontal) velocity direction, so the camera doesn't jitter...
+            // (down the current ground while the ball is still)...
+            let horizontal = Vec3::new(1., 0., 1.);
+            let velocity_direction = (ball_vel.linvel * horizontal)
+                .try_normalize()
+                .unwrap_or_else(|| (current_ground_transform.back() * horizontal).normalize());
+            let direction = chase_direction
+                .get_or_insert(velocity_direction)
+                .lerp(velocity_direction, lerp_fact)
+                .normalize_or_zero();
+            *chase_direction = Some(direction);
+            Transform::from_translation(
+                ball_translation - direction * CHASE_DISTANCE + Vec3::Y * CHASE_HEIGHT,
+            )
+            .looking_at(ball_translation + direction * CHASE_LOOK_AHEAD, Vec3::Y)
+        }
+        CameraMode::TopDown => {
+            Transform::from_translation(ball_translation + Vec3::Y * TOP_DOWN_HEIGHT)
+                .looking_at(ball_translation, Vec3::Z)
+        }
+    };
+    cam_transform.translation = cam_transform
+        .translation
+        .lerp(target_transform.translation, lerp_fact);
+    cam_transform.rotation = cam_transform
+        .rotation
+        .slerp(target_transform.rotation, lerp_fact);
+}
+
+/// Cycle the [`CameraMode`] on [`InputAction::CycleCameraMode`] (`V` by default).
+pub fn cycle_camera_mode(action_input: ActionInput, mut settings_res: ResMut<SettingsResource>) {
+    if action_input.just_pressed(InputAction::CycleCameraMode) {
+        settings_res.camera_mode = settings_res.camera_mode.next();
+    }
+}
+
+/// Switch the camera projection with the [`CameraMode`], orthographic for top-down.
+pub fn apply_camera_projection(
+    mut cameras: Query<(Ref<MyCamera>, &mut Projection)>,
+    settings_res: Res<SettingsResource>,
+) {
+    for (my_camera, mut projection) in cameras.iter_mut() {
+        if !settings_res.is_changed() && !my_camera.is_added() {
+            continue;
+        }
+        let is_orthographic = matches!(*projection, Projection::Orthographic(_));
+        match settings_res.camera_mode {
+            CameraMode::TopDown if !is_orthographic => {
+                *projection = Projection::Orthographic(OrthographicProjection {
+                    scaling_mode: ScalingMode::FixedVertical(TOP_DOWN_VIEW_HEIGHT),
+                    ..default()
+                });
+            }
+            CameraMode::Follow | CameraMode::Chase if is_orthographic => {
+                *projection = Projection::Perspective(default());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Start orbiting from wherever the camera is relative to the ball.
+#[allow(clippy::type_complexity)]
+pub fn start_camera_orbit(
+    cams: Query<&Transform, With<MyCamera>>,
+    balls: Query<&Transform, (With<RollingBall>, Without<MyCamera>)>,
+    mut orbit_res: ResMut<CameraOrbitResource>,
+) {
+    let (Ok(cam_transform), Ok(ball_transform)) = (cams.get_single(), balls.get_single()) else {
+        return;
+    };
+    let offset = cam_transform.translation - ball_transform.translation;
+    let distance = offset.length().max(ORBIT_DISTANCE_RANGE.0);
+    *orbit_res = CameraOrbitResource {
+        yaw: offset.x.atan2(offset.z),
+        pitch: (-offset.y / distance).clamp(-1., 1.).asin(),
+        distance,
+    };
+}
+
+/// Orbit the camera around the ball by dragging the mouse (and zoom with the wheel),
+/// while paused.
+#[allow(clippy::type_complexity)]
+pub fn orbit_camera(
+    mut cams: Query<&mut Transform, With<MyCamera>>,
+    balls: Query<&Transform, (With<RollingBall>, Without<MyCamera>)>,
+    mut orbit_res: ResMut<CameraOrbitResource>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    mouse_btn_input: Res<Input<MouseButton>>,
+    mut egui_contexts: EguiContexts,
+) {
+    let (Ok(mut cam_transform), Ok(ball_transform)) = (cams.get_single_mut(), balls.get_single())
+    else {
+        return;
+    };
+    // don't orbit while using the pause menu...
+    let wants_pointer = egui_contexts.ctx_mut().wants_pointer_input();
+    let drag: Vec2 = mouse_motion_events.iter().map(|event| event.delta).sum();
+    if mouse_btn_input.pressed(MouseButton::Left) && !wants_pointer {
+        orbit_res.yaw -= drag.x * ORBIT_SENSITIVITY;
+        orbit_res.pitch = (orbit_res.pitch - drag.y * ORBIT_SENSITIVITY).clamp(-1.5, -0.05);
+    }
+    for event in mouse_wheel_events.iter() {
+        let scroll = match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / 100.,
+        };
+        orbit_res.distance = (orbit_res.distance * (1. - scroll * 0.1))
+            .clamp(ORBIT_DISTANCE_RANGE.0, ORBIT_DISTANCE_RANGE.1);
+    }
+    let rotation = Quat::from_euler(EulerRot::YXZ, orbit_res.yaw, orbit_res.pitch, 0.);
+    let pivot = ball_transform.translation;
+    *cam_transform = Transform::from_translation(pivot + rotation * Vec3::Z * orbit_res.distance)
+        .looking_at(pivot, Vec3::Y);
+}
+
+/// Add trauma to the [`CameraShake`] of cameras on impacts of the ball,
+/// scaled by the contact force.
+pub fn add_camera_shake_trauma(
+    mut contact_force_events: EventReader<ContactForceEvent>,
+    balls: Query<(), With<RollingBall>>,
+    mut shakes: Query<&mut CameraShake>,
+    settings_res: Res<SettingsResource>,
+) {
+    let impact = contact_force_events
+        .iter()
+        .filter(|event| balls.contains(event.collider1) || balls.contains(event.collider2))
+        .map(|event| {
+            (event.total_force_magnitude - CAMERA_SHAKE_FORCE_THRESHOLD)
+                / (CAMERA_SHAKE_FULL_FORCE - CAMERA_SHAKE_FORCE_THRESHOLD)
+        })
+        .fold(0., f32::max)
+        .clamp(0., 1.);
+    if !settings_res.camera_shake || impact <= 0. {
+        return;
+    }
+    for mut shake in shakes.iter_mut() {
+        shake.trauma = (shake.trauma + impact).min(1.);
+    }
+}
+
+/// Undo the shake applied last frame, so cameras move from their unshaken transform.
+pub fn restore_camera_shake(mut cams: Query<(&mut Transform, &mut CameraShake)>) {
+    for (mut cam_transform, mut shake) in cams.iter_mut() {
+        if shake.applied_offset == Vec3::ZERO && shake.applied_roll == 0. {
+            continue;
+        }
+        cam_transform.translation -= shake.applied_offset;
+        cam_transform.rotate_local_z(-shake.applied_roll);
+        shake.applied_offset = Vec3::ZERO;
+        shake.applied_roll = 0.;
+    }
+}
+
+/// Shake cameras by their (decaying) trauma, after they moved.
+pub fn apply_camera_shake(
+    mut cams: Query<(&mut Transform, &mut CameraShake)>,
+    settings_res: Res<SettingsResource>,
+    time: Res<Time>,
+) {
+    for (mut cam_transform, mut shake) in cams.iter_mut() {
+        shake.trauma = (shake.trauma - SHAKE_TRAUMA_DECAY * time.delta_seconds()).max(0.);
+        if !settings_res.camera_shake || shake.trauma <= 0. {
+            continue;
+        }
+        // squared, so small impacts barely shake...
+        let amount = shake.trauma.powi(2) * settings_res.camera_shake_strength;
+        let t = time.elapsed_seconds() * SHAKE_FREQUENCY;
+        let offset = Vec3::new(shake_noise(t, 0.), shake_noise(t, 1.), shake_noise(t, 2.))
+            * amount
+            * SHAKE_MAX_OFFSET;
+        let roll = shake_noise(t, 3.) * amount * SHAKE_MAX_ROLL;
+        cam_transform.translation += offset;
+        cam_transform.rotate_local_z(roll);
+        shake.applied_offset = offset;
+        shake.applied_roll = roll;
+    }
+}
+
+/// Cheap smooth noise in `-1..=1`, different for each `seed`.
+fn shake_noise(t: f32, seed: f32) -> f32 {
+    ((t + seed * 17.).sin() + (t * 2.3 + seed * 31.).sin() * 0.5) / 1.5
 }
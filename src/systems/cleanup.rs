@@ -1,12 +1,55 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    components::{BelongsToGround, Cleanup, GroundFragment, GroundLayout, GroundMesh, Hazard},
+    resources::{CleanupSettingsResource, PhysicsStepsResource},
+    state::GameState,
+    tuning::GameTuning,
+};
 
-use crate::components::Cleanup;
+/// Collision group of ground fragments, the ball is kept out of it
+/// so disintegrating grounds can't affect the run.
+pub const FRAGMENTS_COLLISION_GROUP: Group = Group::GROUP_2;
 
-pub fn cleanup(mut commands: Commands, mut query: Query<(Entity, &mut Cleanup)>, time: Res<Time>) {
+pub fn cleanup(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Cleanup)>,
+    steps_res: Res<PhysicsStepsResource>,
+    state: Res<State<GameState>>,
+    cleanup_settings_res: Res<CleanupSettingsResource>,
+    tuning: Res<GameTuning>,
+) {
+    // timed by the simulation, so grounds overheat on the same step in a replay...
+    let delta = Duration::from_secs_f32(steps_res.frame_secs);
     for (entity, mut cleanup) in query.iter_mut() {
-        match cleanup.as_mut() {
+        // only changes of the kind of cleanup are detected, not timer ticks...
+        match cleanup.bypass_change_detection() {
             Cleanup::OnTimeout { timer } => {
-                if timer.tick(time.delta()).finished() {
+                // timers are frozen while not in game (ie. paused, game over)
+                if *state.get() != GameState::InGame {
+                    continue;
+                }
+                if timer.tick(delta).finished() {
+                    if cleanup_settings_res.disintegrate_grounds {
+                        *cleanup = Cleanup::Disintegrate {
+                            timer: Timer::from_seconds(tuning.disintegration_secs, TimerMode::Once),
+                        };
+                        continue;
+                    }
+                    let Some(ent_commands) = commands.get_entity(entity) else {
+                        continue;
+                    };
+                    ent_commands.despawn_recursive();
+                }
+            }
+            Cleanup::Disintegrate { timer } => {
+                if *state.get() != GameState::InGame {
+                    continue;
+                }
+                if timer.tick(delta).finished() {
                     let Some(ent_commands) = commands.get_entity(entity) else {
                         continue;
                     };
@@ -34,3 +77,112 @@ pub fn cleanup(mut commands: Commands, mut query: Query<(Entity, &mut Cleanup)>,
         }
     }
 }
+
+/// Break grounds that started to [`Cleanup::Disintegrate`] apart: the ground mesh
+/// is swapped for falling fragments, hazards fall too and everything else stops colliding.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn start_ground_disintegration(
+    mut commands: Commands,
+    grounds: Query<(Entity, &Cleanup, &GroundLayout), Changed<Cleanup>>,
+    colliders: Query<(Entity, &BelongsToGround), (With<Collider>, Without<Hazard>)>,
+    hazards: Query<(Entity, &BelongsToGround), With<Hazard>>,
+    ground_meshes: Query<(Entity, &BelongsToGround, &Handle<StandardMaterial>), With<GroundMesh>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    tuning: Res<GameTuning>,
+) {
+    let fragment_groups =
+        CollisionGroups::new(FRAGMENTS_COLLISION_GROUP, FRAGMENTS_COLLISION_GROUP);
+    for (ground_ent, cleanup, layout) in grounds.iter() {
+        let Cleanup::Disintegrate { .. } = cleanup else {
+            continue;
+        };
+        for (collider_ent, BelongsToGround(collider_ground)) in colliders.iter() {
+            if *collider_ground == ground_ent {
+                commands.entity(collider_ent).remove::<Collider>();
+            }
+        }
+        for (hazard_ent, BelongsToGround(hazard_ground)) in hazards.iter() {
+            if *hazard_ground == ground_ent {
+                commands.entity(hazard_ent).insert((
+                    RigidBody::Dynamic,
+                    fragment_groups,
+                    GroundFragment,
+                ));
+            }
+        }
+        for (mesh_ent, BelongsToGround(mesh_ground), mat_hdl) in ground_meshes.iter() {
+            if *mesh_ground != ground_ent {
+                continue;
+            }
+            commands.entity(mesh_ent).insert(Visibility::Hidden);
+            let Some(fragment_mat) = materials.get(mat_hdl).cloned() else {
+                continue;
+            };
+            // fragments share a copy of the ground material, faded out together...
+            let fragment_mat_hdl = materials.add(fragment_mat);
+            let fragments = tuning.disintegration_fragments.max(1);
+            let fragment_size = Vec3::new(
+                layout.width / fragments as f32,
+                tuning.ground_thickness,
+                tuning.ground_length / fragments as f32,
+            );
+            let fragment_mesh_hdl = meshes
+                .add(shape::Box::new(fragment_size.x, fragment_size.y, fragment_size.z).into());
+            let fragment_collider = Collider::cuboid(
+                fragment_size.x / 2.,
+                fragment_size.y / 2.,
+                fragment_size.z / 2.,
+            );
+            commands.entity(ground_ent).with_children(|commands| {
+                for x in 0..fragments {
+                    for z in 0..fragments {
+                        let translation = Vec3::new(
+                            (x as f32 + 0.5) * fragment_size.x - layout.width / 2.,
+                            0.,
+                            (z as f32 + 0.5) * fragment_size.z - tuning.ground_length / 2.,
+                        );
+                        // fragments are cosmetic so they don't use the `RunRng`...
+                        let random_vec3 =
+                            || Vec3::new(rand::random(), rand::random(), rand::random()) * 2. - 1.;
+                        commands.spawn((
+                            PbrBundle {
+                                mesh: fragment_mesh_hdl.clone(),
+                                material: fragment_mat_hdl.clone(),
+                                transform: Transform::from_translation(translation),
+                                ..default()
+                            },
+                            fragment_collider.clone(),
+                            fragment_groups,
+                            RigidBody::Dynamic,
+                            Velocity {
+                                linvel: translation.normalize_or_zero() + random_vec3(),
+                                angvel: random_vec3() * 2.,
+                            },
+                            GroundFragment,
+                            BelongsToGround(ground_ent),
+                        ));
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Fade out everything on disintegrating grounds.
+pub fn fade_disintegrating_grounds(
+    grounds: Query<&Cleanup>,
+    ground_parts: Query<(&BelongsToGround, &Handle<StandardMaterial>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (BelongsToGround(ground_ent), mat_hdl) in ground_parts.iter() {
+        let Ok(Cleanup::Disintegrate { timer }) = grounds.get(*ground_ent) else {
+            continue;
+        };
+        let Some(mat) = materials.get_mut(mat_hdl) else {
+            continue;
+        };
+        mat.alpha_mode = AlphaMode::Blend;
+        mat.base_color.set_a(timer.percent_left());
+    }
+}
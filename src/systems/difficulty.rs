@@ -0,0 +1,24 @@
+use bevy::prelude::*;
+
+use crate::{
+    resources::{DifficultyResource, ScoresResource},
+    tuning::GameTuning,
+};
+
+/// Ramp the difficulty up along the tuned curve as the run goes on
+/// (timed by the stopwatch, which follows the simulated time).
+pub fn update_difficulty(
+    scoring_res: Res<ScoresResource>,
+    tuning: Res<GameTuning>,
+    mut difficulty_res: ResMut<DifficultyResource>,
+) {
+    let elapsed_secs = scoring_res
+        .stopwatch
+        .as_ref()
+        .map(|stopwatch| stopwatch.elapsed_secs())
+        .unwrap_or_default();
+    let level = tuning
+        .difficulty
+        .level(scoring_res.grounds_passed, elapsed_secs);
+    *difficulty_res = DifficultyResource::new(&tuning, level);
+}
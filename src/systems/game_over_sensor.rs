@@ -46,7 +46,7 @@ pub fn handle_ground_game_over_sensor(
             continue;
         };
         if is_intersecting {
-            game_event.send(SceneEvent::Restart);
+            game_event.send(SceneEvent::GameOver);
         }
     }
 }
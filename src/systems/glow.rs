@@ -0,0 +1,57 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    components::{Cleanup, Ground, RollingBall},
+    materials::glowy::{procedural_env_texture, GlowyEnvTexture, GlowyMaterial},
+    resources::GroundsResource,
+};
+
+use super::ground::current_overheat_urgency;
+
+/// Ball speed the glow is at its brightest/widest.
+const BALL_GLOW_FULL_SPEED: f32 = 15.;
+/// Glow color of the ball about to overheat a ground.
+const BALL_GLOW_OVERHEAT_COLOR: Color = Color::rgb_linear(1.0, 0.05, 0.004);
+
+pub fn setup_glowy_env_texture(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    commands.insert_resource(GlowyEnvTexture(images.add(procedural_env_texture())));
+}
+
+/// Pulse the ball's glow, brighter and faster with speed and redder as the
+/// current ground is about to overheat. Also gives it the env texture if it has none.
+pub fn update_ball_glow(
+    balls: Query<(&Handle<GlowyMaterial>, &Velocity), With<RollingBall>>,
+    grounds: Query<&Cleanup, With<Ground>>,
+    ground_res: Res<GroundsResource>,
+    env_texture: Res<GlowyEnvTexture>,
+    mut materials: ResMut<Assets<GlowyMaterial>>,
+    time: Res<Time>,
+    mut pulse_phase: Local<f32>,
+) {
+    let Ok((mat_hdl, ball_vel)) = balls.get_single() else {
+        return;
+    };
+    let Some(mat) = materials.get_mut(mat_hdl) else {
+        return;
+    };
+    if mat.env_texture.is_none() {
+        mat.env_texture = Some(env_texture.0.clone());
+    }
+    let speed_fraction = (ball_vel.linvel.length() / BALL_GLOW_FULL_SPEED).min(1.);
+    let urgency = current_overheat_urgency(&grounds, &ground_res);
+    // pulse rate (in Hz) follows speed and urgency, accumulated to avoid jumps...
+    let pulse_rate = 0.5 + speed_fraction * 1.5 + urgency * 3.;
+    *pulse_phase = (*pulse_phase + pulse_rate * time.delta_seconds()).fract();
+    let pulse = (*pulse_phase * TAU).sin() * 0.5 + 0.5;
+    let base = GlowyMaterial::default();
+    let glow_color = Vec4::from(base.glow_color.as_linear_rgba_f32()).lerp(
+        Vec4::from(BALL_GLOW_OVERHEAT_COLOR.as_linear_rgba_f32()),
+        urgency,
+    );
+    mat.glow_color = Color::rgba_linear(glow_color.x, glow_color.y, glow_color.z, glow_color.w);
+    mat.glow_power = base.glow_power / (1. + speed_fraction);
+    mat.intensity = base.intensity * (1. + speed_fraction) * (1. + pulse * (0.2 + urgency));
+}
@@ -3,12 +3,13 @@ use bevy_rapier3d::prelude::*;
 
 use crate::{
     components::{
-        BelongsToGround, Cleanup, GameOverSensor, Ground, GroundMesh, GroundMidSensor,
-        GroundSurfaceSensor, RollingBall,
+        BelongsToGround, Cleanup, GameOverSensor, Ground, GroundIndex, GroundLayout, GroundMesh,
+        GroundMidSensor, GroundSurfaceSensor, RollingBall,
     },
-    constants::{GROUND_ANGLE, GROUND_LENGTH, GROUND_OVERHEAT_DURATION_SECS, GROUND_THICKNESS},
+    constants::GROUND_OVERHEAT_WARNING_FRACTION,
     events::SceneEvent,
-    resources::GroundsResource,
+    resources::{DifficultyResource, GroundsResource, RunRng},
+    tuning::GameTuning,
 };
 
 use super::scene::spawn_ground;
@@ -19,6 +20,7 @@ pub fn handle_ground_sensor(
     balls: Query<Entity, (With<RollingBall>, With<Collider>)>,
     ground_sensors: Query<(Entity, &BelongsToGround), (With<GroundSurfaceSensor>, With<Collider>)>,
     mut ground_res: ResMut<GroundsResource>,
+    difficulty_res: Res<DifficultyResource>,
     rapier_context: Res<RapierContext>,
 ) {
     let Ok(ball_ent) = balls.get_single() else {
@@ -43,7 +45,7 @@ pub fn handle_ground_sensor(
         ground_res.next_ground = None;
         if let Some(active_ground) = active_ground {
             commands.entity(active_ground).insert(Cleanup::OnTimeout {
-                timer: Timer::from_seconds(GROUND_OVERHEAT_DURATION_SECS, TimerMode::Once),
+                timer: Timer::from_seconds(difficulty_res.overheat_duration_secs, TimerMode::Once),
             });
         }
     }
@@ -54,8 +56,10 @@ pub fn handle_mid_ground_sensor(
     mut commands: Commands,
     balls: Query<Entity, (With<RollingBall>, With<Collider>)>,
     ground_mid_sensors: Query<(Entity, &BelongsToGround), (With<GroundMidSensor>, With<Collider>)>,
-    query_grounds: Query<&Transform, With<Ground>>,
+    query_grounds: Query<(&Transform, &GroundIndex, &GroundLayout), With<Ground>>,
     mut ground_res: ResMut<GroundsResource>,
+    mut run_rng: ResMut<RunRng>,
+    tuning: Res<GameTuning>,
     rapier_context: Res<RapierContext>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
@@ -72,21 +76,28 @@ pub fn handle_mid_ground_sensor(
             && ground_res.next_ground.is_none()
         {
             // spawn new ground relative to prev ground transform...
-            let mut transform = Transform::default();
-            if let Some(current_ground) = ground_res.current_ground {
-                let Ok(current_transform) = query_grounds.get(current_ground) else {
-                    continue;
-                };
-                transform = *current_transform;
-            }
-            let Some(ground_ent) = spawn_ground(&mut commands, &mut meshes, &mut materials) else {
+            let Some(current_ground) = ground_res.current_ground else {
+                continue;
+            };
+            let Ok((current_transform, GroundIndex(current_index), current_layout)) =
+                query_grounds.get(current_ground)
+            else {
+                continue;
+            };
+            let index = GroundIndex(current_index + 1);
+            let layout = GroundLayout::generate(&mut run_rng.0, &tuning, index);
+            let transform =
+                next_ground_transform(current_transform, current_layout, &layout, &tuning);
+            let Some(ground_ent) = spawn_ground(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                &tuning,
+                index,
+                layout,
+            ) else {
                 continue;
             };
-            // rotate by 45 deg...
-            transform.rotation = Quat::from_axis_angle(Vec3::X, GROUND_ANGLE);
-            transform.translation.y +=
-                -(GROUND_LENGTH / GROUND_ANGLE.cos()) * 0.385 * GROUND_THICKNESS;
-            transform.translation.z += GROUND_LENGTH - 0.2;
             commands
                 .entity(ground_ent)
                 .insert(TransformBundle::from_transform(transform));
@@ -97,6 +108,23 @@ pub fn handle_mid_ground_sensor(
     }
 }
 
+/// Transform of a ground with `layout` following the one at `transform`, tilted and
+/// placed so its top edge meets the bottom edge of the previous one.
+pub fn next_ground_transform(
+    transform: &Transform,
+    layout: &GroundLayout,
+    next_layout: &GroundLayout,
+    tuning: &GameTuning,
+) -> Transform {
+    let mut next_transform = *transform;
+    next_transform.rotation = Quat::from_axis_angle(Vec3::X, next_layout.angle);
+    next_transform.translation.x += next_layout.lateral_offset;
+    next_transform.translation.y -=
+        tuning.ground_length / 2. * (layout.angle.sin() + next_layout.angle.sin());
+    next_transform.translation.z += tuning.ground_length - tuning.ground_overlap;
+    next_transform
+}
+
 #[allow(clippy::type_complexity)]
 pub fn color_grounds(
     grounds: Query<&Cleanup, With<Ground>>,
@@ -113,11 +141,35 @@ pub fn color_grounds(
             continue;
         };
         let mut new_color = mat.base_color.as_hsla_f32();
-        new_color[0] = Color::GREEN.as_hsla_f32()[0] * remaining / GROUND_OVERHEAT_DURATION_SECS;
+        new_color[0] = Color::GREEN.as_hsla_f32()[0] * remaining / timer.duration().as_secs_f32();
         mat.base_color = Color::hsla(new_color[0], new_color[1], new_color[2], new_color[3]);
     }
 }
 
+/// How close a ground is to overheating, `0` until its overheat `timer` is below
+/// [`GROUND_OVERHEAT_WARNING_FRACTION`] going up to `1` as it runs out.
+pub fn overheat_urgency(timer: &Timer) -> f32 {
+    if timer.finished() {
+        return 0.;
+    }
+    let remaining_fraction = timer.remaining_secs() / timer.duration().as_secs_f32();
+    (1. - remaining_fraction / GROUND_OVERHEAT_WARNING_FRACTION).max(0.)
+}
+
+/// [`overheat_urgency`] of the current ground.
+pub fn current_overheat_urgency(
+    grounds: &Query<&Cleanup, With<Ground>>,
+    ground_res: &GroundsResource,
+) -> f32 {
+    let Some(current_ground) = ground_res.current_ground else {
+        return 0.;
+    };
+    let Ok(Cleanup::OnTimeout { timer }) = grounds.get(current_ground) else {
+        return 0.;
+    };
+    overheat_urgency(timer)
+}
+
 // pub fn mark_cleanup_prev_grounds(mut commands: Commands, ground_res: Res<GroundsResource>) {
 //     if !ground_res.is_changed() {
 //         return;
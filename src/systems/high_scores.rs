@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+use crate::{constants::HIGH_SCORES_STORAGE_KEY, persistence, resources::HighScoresResource};
+
+/// Load the saved high scores table (if any) into [`HighScoresResource`].
+pub fn load_high_scores(mut high_scores_res: ResMut<HighScoresResource>) {
+    let Some(saved) = persistence::load(HIGH_SCORES_STORAGE_KEY) else {
+        return;
+    };
+    match ron::from_str::<HighScoresResource>(&saved) {
+        Ok(loaded) => *high_scores_res = loaded,
+        Err(err) => warn!("Failed to parse saved high scores: {}", err),
+    }
+}
+
+/// Save the high scores table whenever it changes.
+pub fn save_high_scores(high_scores_res: Res<HighScoresResource>) {
+    if !high_scores_res.is_changed() || high_scores_res.is_added() {
+        return;
+    }
+    let serialized = match ron::to_string(high_scores_res.as_ref()) {
+        Ok(serialized) => serialized,
+        Err(err) => {
+            warn!("Failed to serialize high scores: {}", err);
+            return;
+        }
+    };
+    if persistence::save(HIGH_SCORES_STORAGE_KEY, &serialized).is_none() {
+        warn!("Failed to save high scores");
+    }
+}
+
+/// Format a unix timestamp (in secs) as a `YYYY-MM-DD` date (UTC).
+pub fn format_date(timestamp_secs: u64) -> String {
+    // ref: http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = (timestamp_secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_portion = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_portion + 2) / 5 + 1;
+    let month = if month_portion < 10 {
+        month_portion + 3
+    } else {
+        month_portion - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -1,7 +1,177 @@
-use bevy::prelude::*;
+use bevy::{app::AppExit, prelude::*};
+use bevy_egui::{
+    egui::{self, Color32, RichText},
+    EguiContexts,
+};
 
-use crate::state::GameState;
+use crate::{
+    constants::GAME_TITLE,
+    events::SceneEvent,
+    input::{ActionInput, InputAction},
+    resources::{ReplayResource, RunSeed, ScoresResource},
+    state::GameState,
+};
+
+use super::{egui::get_default_egui_frame, replay::load_last_replay};
 
 pub fn auto_start_game_on_menu(mut next_state: ResMut<NextState<GameState>>) {
     next_state.set(GameState::SceneLoading);
 }
+
+/// Toggle pause on [`InputAction::Pause`] (`Escape` by default).
+pub fn handle_pause_input(
+    action_input: ActionInput,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !action_input.just_pressed(InputAction::Pause) {
+        return;
+    }
+    match state.get() {
+        GameState::InGame => next_state.set(GameState::Paused),
+        GameState::Paused => next_state.set(GameState::InGame),
+        _ => {}
+    }
+}
+
+pub fn display_main_menu(
+    mut egui_contexts: EguiContexts,
+    mut run_seed: ResMut<RunSeed>,
+    mut replay_res: ResMut<ReplayResource>,
+    mut scene_events: EventWriter<SceneEvent>,
+    mut app_exit_events: EventWriter<AppExit>,
+    mut seed_input: Local<String>,
+) {
+    let frame = get_default_egui_frame();
+    egui::Window::new("Main Menu")
+        .title_bar(false)
+        .collapsible(false)
+        .movable(false)
+        .resizable(false)
+        .frame(frame)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(RichText::new(GAME_TITLE).heading());
+                ui.separator();
+                // seed (for challenge/reproducible runs)...
+                ui.checkbox(&mut run_seed.reroll, "Random seed");
+                if !run_seed.reroll {
+                    ui.horizontal(|ui| {
+                        ui.label("Seed");
+                        if seed_input.is_empty() {
+                            *seed_input = run_seed.seed.to_string();
+                        }
+                        if ui.text_edit_singleline(&mut *seed_input).changed() {
+                            if let Ok(seed) = seed_input.trim().parse() {
+                                run_seed.seed = seed;
+                            }
+                        }
+                    });
+                }
+                ui.separator();
+                if ui.button(RichText::new("Start").size(17.)).clicked() {
+                    scene_events.send(SceneEvent::Start);
+                }
+                if ui
+                    .button(RichText::new("Watch Last Replay").size(17.))
+                    .clicked()
+                {
+                    if let Some(replay) = load_last_replay() {
+                        replay_res.play(replay);
+                        scene_events.send(SceneEvent::Start);
+                    }
+                }
+                // no quitting a browser tab...
+                if cfg!(not(target_arch = "wasm32"))
+                    && ui.button(RichText::new("Quit").size(17.)).clicked()
+                {
+                    app_exit_events.send(AppExit);
+                }
+            });
+        });
+}
+
+pub fn display_pause_menu(
+    mut egui_contexts: EguiContexts,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut scene_events: EventWriter<SceneEvent>,
+) {
+    let frame = get_default_egui_frame();
+    egui::Window::new("Paused")
+        .title_bar(false)
+        .collapsible(false)
+        .movable(false)
+        .resizable(false)
+        .frame(frame)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(RichText::new("Paused").heading());
+                ui.separator();
+                if ui.button(RichText::new("Resume").size(17.)).clicked() {
+                    next_state.set(GameState::InGame);
+                }
+                if ui.button(RichText::new("Restart").size(17.)).clicked() {
+                    scene_events.send(SceneEvent::Restart);
+                }
+                if ui.button(RichText::new("Main Menu").size(17.)).clicked() {
+                    scene_events.send(SceneEvent::Quit);
+                }
+                ui.separator();
+                ui.label(RichText::new("Drag to orbit the camera, scroll to zoom").small());
+            });
+        });
+}
+
+pub fn display_game_over(
+    mut egui_contexts: EguiContexts,
+    scoring_res: Res<ScoresResource>,
+    run_seed: Res<RunSeed>,
+    mut scene_events: EventWriter<SceneEvent>,
+) {
+    let ScoresResource {
+        stopwatch: Some(stopwatch),
+        grounds_passed,
+        was_new_best,
+    } = scoring_res.as_ref()
+    else {
+        return;
+    };
+    let elapsed_secs = stopwatch.elapsed_secs();
+    let frame = get_default_egui_frame();
+    egui::Window::new("Game Over")
+        .title_bar(false)
+        .collapsible(false)
+        .movable(false)
+        .resizable(false)
+        .frame(frame)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(RichText::new("Game Over").heading());
+                ui.label("The particle escaped the panels!");
+                ui.separator();
+                ui.label(
+                    RichText::new(format!(
+                        "Time: {:02.0}:{:02.0}  Panels: {}",
+                        elapsed_secs / 60.,
+                        elapsed_secs % 60.,
+                        grounds_passed
+                    ))
+                    .size(17.),
+                );
+                if *was_new_best {
+                    ui.label(RichText::new("New personal best!").color(Color32::GOLD));
+                }
+                ui.label(format!("Seed: {}", run_seed.seed));
+                ui.separator();
+                if ui.button(RichText::new("Restart").size(17.)).clicked() {
+                    scene_events.send(SceneEvent::Restart);
+                }
+                if ui.button(RichText::new("Main Menu").size(17.)).clicked() {
+                    scene_events.send(SceneEvent::Quit);
+                }
+            });
+        });
+}
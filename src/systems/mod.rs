@@ -1,14 +1,24 @@
+pub mod audio;
 pub mod basic;
 pub mod camera;
 pub mod cleanup;
 pub mod credits;
+pub mod difficulty;
 pub mod egui;
 pub mod game_over_sensor;
+pub mod glow;
 pub mod ground;
+pub mod high_scores;
 pub mod lights;
 pub mod menu;
+pub mod music;
+pub mod particles;
+pub mod replay;
 pub mod scene;
 pub mod scoring;
 pub mod settings;
+pub mod tuning;
+pub mod wall_budget;
+pub mod wall_input;
 pub mod walls;
 pub mod window;
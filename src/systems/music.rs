@@ -0,0 +1,169 @@
+use bevy::{
+    audio::{AudioSinkPlayback, Volume},
+    prelude::*,
+};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    audio::{music::MusicPattern, synth::Waveform},
+    components::{Cleanup, Ground, MusicLayer, MusicLayerKind, RollingBall},
+    resources::{AudioMixerResource, GroundsResource, ScoresResource},
+    state::GameState,
+};
+
+use super::ground::current_overheat_urgency;
+
+/// Step length shared by all layers (16th notes at 120 bpm).
+const MUSIC_STEP_SECS: f32 = 0.125;
+/// Panels passed for all progress driven layers to be in.
+const MUSIC_GROUNDS_TO_FULL: f32 = 20.;
+/// Ball speed the speed driven layer is fully in at (10x the scoreboard speed).
+const MUSIC_SPEED_FULL: f32 = 15.;
+/// How fast layers fade towards their target level.
+const MUSIC_FADE_RATE: f32 = 1.5;
+
+const MUSIC_LAYERS: [MusicLayerKind; 5] = [
+    MusicLayerKind::Pad,
+    MusicLayerKind::Bass,
+    MusicLayerKind::Arpeggio,
+    MusicLayerKind::Hats,
+    MusicLayerKind::Alarm,
+];
+
+/// Frequency of the note `semitones` away from A4.
+fn note(semitones: i32) -> f32 {
+    440. * 2_f32.powf(semitones as f32 / 12.)
+}
+
+fn notes(semitones: &[Option<i32>]) -> Vec<Option<f32>> {
+    semitones.iter().map(|step| step.map(note)).collect()
+}
+
+/// Pattern of a layer, all looping over an `Am F C G` progression (or a multiple of it).
+fn layer_pattern(kind: MusicLayerKind) -> MusicPattern {
+    // chord roots, relative to A4...
+    const ROOTS: [i32; 4] = [0, -4, 3, -2];
+    // chord tones (for the arpeggio), relative to the root...
+    const THIRDS: [i32; 4] = [3, 4, 4, 4];
+    match kind {
+        MusicLayerKind::Pad => MusicPattern {
+            waveform: Waveform::Sine,
+            step_secs: MUSIC_STEP_SECS,
+            note_secs: MUSIC_STEP_SECS,
+            notes: notes(
+                &ROOTS
+                    .iter()
+                    .flat_map(|root| [Some(root - 24); 8])
+                    .collect::<Vec<_>>(),
+            ),
+        },
+        MusicLayerKind::Bass => MusicPattern {
+            waveform: Waveform::Saw,
+            step_secs: MUSIC_STEP_SECS,
+            note_secs: 0.1,
+            notes: notes(
+                &ROOTS
+                    .iter()
+                    .flat_map(|root| {
+                        let root = root - 36;
+                        [
+                            Some(root),
+                            None,
+                            Some(root),
+                            None,
+                            Some(root + 12),
+                            None,
+                            Some(root),
+                            None,
+                        ]
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+        },
+        MusicLayerKind::Arpeggio => MusicPattern {
+            waveform: Waveform::Square,
+            step_secs: MUSIC_STEP_SECS,
+            note_secs: 0.06,
+            notes: notes(
+                &ROOTS
+                    .iter()
+                    .zip(THIRDS)
+                    .flat_map(|(root, third)| {
+                        let arp = [*root, root + third, root + 7, root + third];
+                        [arp, arp].concat().into_iter().map(Some)
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+        },
+        MusicLayerKind::Hats => MusicPattern {
+            waveform: Waveform::Noise,
+            step_secs: MUSIC_STEP_SECS,
+            note_secs: 0.03,
+            notes: notes(&[None, None, Some(0), None, None, None, Some(0), Some(0)]),
+        },
+        MusicLayerKind::Alarm => MusicPattern {
+            waveform: Waveform::Square,
+            step_secs: MUSIC_STEP_SECS,
+            note_secs: 0.1,
+            notes: notes(&[Some(7), None, Some(8), None]),
+        },
+    }
+}
+
+/// Volume of a layer at full level, keeps the layers balanced.
+fn layer_gain(kind: MusicLayerKind) -> f32 {
+    match kind {
+        MusicLayerKind::Pad => 0.2,
+        MusicLayerKind::Bass => 0.15,
+        MusicLayerKind::Arpeggio => 0.06,
+        MusicLayerKind::Hats => 0.08,
+        MusicLayerKind::Alarm => 0.06,
+    }
+}
+
+/// Start all the music layers (silent) together, so they stay in time.
+pub fn setup_music(mut commands: Commands, mut patterns: ResMut<Assets<MusicPattern>>) {
+    for kind in MUSIC_LAYERS {
+        commands.spawn((
+            AudioSourceBundle {
+                source: patterns.add(layer_pattern(kind)),
+                settings: PlaybackSettings::LOOP.with_volume(Volume::new_relative(0.)),
+            },
+            MusicLayer { kind, level: 0. },
+        ));
+    }
+}
+
+/// Fade the music layers in/out with the intensity of the run:
+/// panels passed, ball speed and how close the current panel is to overheating.
+#[allow(clippy::too_many_arguments)]
+pub fn update_music_layers(
+    mut layers: Query<(&AudioSink, &mut MusicLayer)>,
+    balls: Query<&Velocity, With<RollingBall>>,
+    grounds: Query<&Cleanup, With<Ground>>,
+    ground_res: Res<GroundsResource>,
+    scores_res: Res<ScoresResource>,
+    state: Res<State<GameState>>,
+    mixer_res: Res<AudioMixerResource>,
+    time: Res<Time>,
+) {
+    let in_game = *state.get() == GameState::InGame;
+    let progress = (scores_res.grounds_passed as f32 / MUSIC_GROUNDS_TO_FULL).min(1.);
+    let speed = balls
+        .get_single()
+        .map_or(0., |ball_vel| ball_vel.linvel.length());
+    let urgency = current_overheat_urgency(&grounds, &ground_res);
+    let fade = 1. - (-MUSIC_FADE_RATE * time.delta_seconds()).exp();
+    for (sink, mut layer) in layers.iter_mut() {
+        let target_level = match layer.kind {
+            MusicLayerKind::Pad => 1.,
+            _ if !in_game => 0.,
+            MusicLayerKind::Bass => (progress * 4.).min(1.),
+            MusicLayerKind::Arpeggio => ((progress - 0.25) / 0.75).clamp(0., 1.),
+            MusicLayerKind::Hats => (speed / MUSIC_SPEED_FULL).min(1.),
+            MusicLayerKind::Alarm => urgency,
+        };
+        layer.level += (target_level - layer.level) * fade;
+        sink.set_volume(layer.level * layer_gain(layer.kind) * mixer_res.music);
+    }
+}
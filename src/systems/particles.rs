@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{Cleanup, Ground, GroundLayout, RollingBall},
+    plugins::ParticleEmitter,
+    tuning::GameTuning,
+};
+
+use super::ground::overheat_urgency;
+
+/// Sparks spawned per sec by a ground about to overheat.
+const OVERHEAT_SPARKS_RATE_MAX: f32 = 120.;
+
+/// Give newly spawned balls a glowing trail.
+pub fn add_ball_trails(mut commands: Commands, balls: Query<Entity, Added<RollingBall>>) {
+    for ball_ent in balls.iter() {
+        commands.entity(ball_ent).insert(ParticleEmitter {
+            rate: 40.,
+            lifetime_secs: 0.6,
+            spawn_extents: Vec3::splat(0.2),
+            velocity_spread: 0.3,
+            colors: (Color::rgb(0.4, 0.8, 1.0), Color::rgba(0.6, 0.2, 1.0, 0.)),
+            size: 0.15,
+            ..default()
+        });
+    }
+}
+
+/// Give newly spawned grounds a (for now idle) emitter of overheat sparks over their surface.
+pub fn add_ground_sparks(
+    mut commands: Commands,
+    grounds: Query<(Entity, &GroundLayout), Added<GroundLayout>>,
+    tuning: Res<GameTuning>,
+) {
+    for (ground_ent, layout) in grounds.iter() {
+        commands.entity(ground_ent).insert(ParticleEmitter {
+            lifetime_secs: 0.8,
+            spawn_offset: Vec3::Y * tuning.ground_thickness / 2.,
+            spawn_extents: Vec3::new(layout.width / 2., 0., tuning.ground_length / 2.),
+            velocity: Vec3::Y * 2.,
+            velocity_spread: 1.,
+            acceleration: Vec3::Y * -4.,
+            colors: (Color::rgb(1.0, 0.8, 0.3), Color::rgba(1.0, 0.1, 0.0, 0.)),
+            size: 0.2,
+            ..default()
+        });
+    }
+}
+
+/// Emit more sparks the closer a ground is to overheating.
+pub fn update_ground_sparks(mut grounds: Query<(&Cleanup, &mut ParticleEmitter), With<Ground>>) {
+    for (cleanup, mut emitter) in grounds.iter_mut() {
+        let urgency = match cleanup {
+            Cleanup::OnTimeout { timer } => overheat_urgency(timer),
+            _ => 0.,
+        };
+        emitter.rate = urgency * OVERHEAT_SPARKS_RATE_MAX;
+    }
+}
+
+/// Burst of particles where the ball fell off, on game over.
+pub fn spawn_game_over_burst(
+    mut commands: Commands,
+    balls: Query<&GlobalTransform, With<RollingBall>>,
+) {
+    let Ok(ball_transform) = balls.get_single() else {
+        return;
+    };
+    commands.spawn((
+        TransformBundle::from_transform(Transform::from_translation(ball_transform.translation())),
+        ParticleEmitter {
+            burst: 200,
+            lifetime_secs: 1.2,
+            spawn_extents: Vec3::splat(0.3),
+            velocity_spread: 6.,
+            acceleration: Vec3::Y * -9.81,
+            colors: (Color::WHITE, Color::rgba(0.8, 0.2, 1.0, 0.)),
+            size: 0.3,
+            despawn_when_done: true,
+            ..default()
+        },
+    ));
+}
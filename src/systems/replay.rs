@@ -0,0 +1,229 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    components::{BelongsToGround, Ground, GroundIndex, Wall, WallKind},
+    constants::REPLAY_STORAGE_KEY,
+    events::WallEvent,
+    persistence,
+    resources::{
+        PhysicsStepsResource, Replay, ReplayMode, ReplayResource, ReplayWallDraw,
+        ReplayWallRemoval, RunSeed,
+    },
+};
+
+/// Run condition for systems reading the player's input.
+pub fn is_replay_recording(replay_res: Res<ReplayResource>) -> bool {
+    replay_res.mode == ReplayMode::Record
+}
+
+/// Reset the replay for a new run, to be run after the run is seeded.
+///
+/// Playback switches to a fixed timestep, so each recorded step lines up with a frame.
+pub fn reset_replay(
+    mut replay_res: ResMut<ReplayResource>,
+    run_seed: Res<RunSeed>,
+    mut rapier_conf: ResMut<RapierConfiguration>,
+) {
+    match replay_res.mode {
+        ReplayMode::Record => {
+            replay_res.replay = Replay {
+                seed: run_seed.seed,
+                wall_draws: vec![],
+                wall_removals: vec![],
+            };
+            if let Some(timestep_mode) = replay_res.record_timestep_mode.take() {
+                rapier_conf.timestep_mode = timestep_mode;
+            }
+        }
+        ReplayMode::Playback => {
+            let timestep_mode = *replay_res
+                .record_timestep_mode
+                .get_or_insert(rapier_conf.timestep_mode);
+            rapier_conf.timestep_mode = playback_timestep_mode(timestep_mode);
+        }
+    }
+}
+
+/// Fixed timestep simulating the same steps as the recording `timestep_mode`.
+fn playback_timestep_mode(timestep_mode: TimestepMode) -> TimestepMode {
+    match timestep_mode {
+        TimestepMode::Fixed { .. } => timestep_mode,
+        TimestepMode::Interpolated {
+            dt,
+            time_scale,
+            substeps,
+        } => TimestepMode::Fixed {
+            dt: dt * time_scale,
+            substeps,
+        },
+        // steps vary in length, so the playback can only be close...
+        TimestepMode::Variable {
+            max_dt,
+            time_scale,
+            substeps,
+        } => TimestepMode::Fixed {
+            dt: max_dt * time_scale,
+            substeps,
+        },
+    }
+}
+
+/// Record [`WallEvent::Draw`]s along with the last hovered ground/transform,
+/// and [`WallEvent::Remove`]s along with where the removed wall stood.
+pub fn record_wall_events(
+    grounds: Query<&GroundIndex, With<Ground>>,
+    walls: Query<(&Transform, &BelongsToGround), With<Wall>>,
+    mut wall_events: EventReader<WallEvent>,
+    mut replay_res: ResMut<ReplayResource>,
+    steps_res: Res<PhysicsStepsResource>,
+    mut last_hover: Local<Option<(usize, Transform, WallKind)>>,
+) {
+    for event in wall_events.iter() {
+        if replay_res.mode != ReplayMode::Record {
+            continue;
+        }
+        match event {
+            WallEvent::HoverUpdate {
+                ground,
+                transform,
+                kind,
+            } => {
+                let Ok(GroundIndex(ground_index)) = grounds.get(*ground) else {
+                    continue;
+                };
+                *last_hover = Some((*ground_index, *transform, *kind));
+            }
+            WallEvent::HoverStop => {
+                *last_hover = None;
+            }
+            WallEvent::Draw => {
+                let Some((ground_index, transform, kind)) = *last_hover else {
+                    continue;
+                };
+                replay_res.replay.wall_draws.push(ReplayWallDraw {
+                    step: steps_res.steps,
+                    ground_index,
+                    transform,
+                    kind,
+                });
+            }
+            WallEvent::Remove { wall } => {
+                let Ok((transform, BelongsToGround(ground))) = walls.get(*wall) else {
+                    continue;
+                };
+                let Ok(GroundIndex(ground_index)) = grounds.get(*ground) else {
+                    continue;
+                };
+                replay_res.replay.wall_removals.push(ReplayWallRemoval {
+                    step: steps_res.steps,
+                    ground_index: *ground_index,
+                    translation: transform.translation,
+                });
+            }
+        }
+    }
+}
+
+/// Feed the recorded wall draws and removals back as [`WallEvent`]s.
+///
+/// Draws are sent along with their hover, as the player's input sends them.
+/// Removals target the wall closest to where the removed one stood on its ground.
+pub fn playback_wall_events(
+    grounds: Query<(Entity, &GroundIndex), With<Ground>>,
+    walls: Query<(Entity, &Transform, &BelongsToGround), With<Wall>>,
+    replay_res: Res<ReplayResource>,
+    steps_res: Res<PhysicsStepsResource>,
+    mut wall_events: EventWriter<WallEvent>,
+) {
+    if replay_res.mode != ReplayMode::Playback {
+        return;
+    }
+    let step = steps_res.steps;
+    for wall_draw in replay_res.replay.wall_draws.iter() {
+        let ReplayWallDraw {
+            step: draw_step,
+            ground_index,
+            transform,
+            kind,
+        } = wall_draw;
+        if *draw_step != step {
+            continue;
+        }
+        let Some((ground, _)) = grounds.iter().find(|(_, index)| index.0 == *ground_index) else {
+            warn!(
+                "Replay diverged, ground {} not found on step {}",
+                ground_index, step
+            );
+            continue;
+        };
+        wall_events.send(WallEvent::HoverUpdate {
+            ground,
+            transform: *transform,
+            kind: *kind,
+        });
+        wall_events.send(WallEvent::Draw);
+    }
+    for wall_removal in replay_res.replay.wall_removals.iter() {
+        let ReplayWallRemoval {
+            step: removal_step,
+            ground_index,
+            translation,
+        } = wall_removal;
+        if *removal_step != step {
+            continue;
+        }
+        let Some((ground, _)) = grounds.iter().find(|(_, index)| index.0 == *ground_index) else {
+            warn!(
+                "Replay diverged, ground {} not found on step {}",
+                ground_index, step
+            );
+            continue;
+        };
+        let Some((wall, _, _)) = walls
+            .iter()
+            .filter(|(_, _, BelongsToGround(wall_ground))| *wall_ground == ground)
+            .min_by(|(_, a, _), (_, b, _)| {
+                let dist_a = a.translation.distance_squared(*translation);
+                let dist_b = b.translation.distance_squared(*translation);
+                dist_a.total_cmp(&dist_b)
+            })
+        else {
+            warn!(
+                "Replay diverged, no wall to remove on ground {} on step {}",
+                ground_index, step
+            );
+            continue;
+        };
+        wall_events.send(WallEvent::Remove { wall });
+    }
+}
+
+/// Save the recorded replay of the run that just ended.
+pub fn save_replay(replay_res: Res<ReplayResource>) {
+    if replay_res.mode != ReplayMode::Record {
+        return;
+    }
+    let serialized = match ron::to_string(&replay_res.replay) {
+        Ok(serialized) => serialized,
+        Err(err) => {
+            warn!("Failed to serialize replay: {}", err);
+            return;
+        }
+    };
+    if persistence::save(REPLAY_STORAGE_KEY, &serialized).is_none() {
+        warn!("Failed to save replay");
+    }
+}
+
+/// Load the replay of the last recorded run, if any.
+pub fn load_last_replay() -> Option<Replay> {
+    let saved = persistence::load(REPLAY_STORAGE_KEY)?;
+    match ron::from_str::<Replay>(&saved) {
+        Ok(replay) => Some(replay),
+        Err(err) => {
+            warn!("Failed to parse saved replay: {}", err);
+            None
+        }
+    }
+}
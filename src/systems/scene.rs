@@ -1,40 +1,75 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
 
 use crate::{
     components::{
-        BelongsToGround, Cleanup, GameOverSensor, Ground, GroundMesh, GroundMidSensor,
-        GroundSurfaceSensor, MyCamera, MyLight, RollingBall,
+        BelongsToGround, CameraShake, Cleanup, GameOverSensor, Ground, GroundIndex, GroundLayout,
+        GroundMesh, GroundMidSensor, GroundSurfaceSensor, Hazard, MyCamera, MyLight, RollingBall,
     },
-    constants::{GROUND_ANGLE, GROUND_LENGTH, GROUND_THICKNESS, GROUND_WIDTH},
+    constants::CAMERA_SHAKE_FORCE_THRESHOLD,
     events::SceneEvent,
     materials::glowy::GlowyMaterial,
-    resources::{GroundsResource, PreviousScoresRes, ScoresResource},
+    persistence,
+    resources::{
+        GroundsResource, HighScore, HighScoresResource, PhysicsStepsResource, ReplayMode,
+        ReplayResource, RunRng, RunSeed, SceneLoadingTimer, ScoresResource, SettingsResource,
+    },
     state::GameState,
+    tuning::GameTuning,
 };
 
+#[cfg(feature = "dev")]
+use crate::plugins::TrackableCamera;
+
+use super::cleanup::FRAGMENTS_COLLISION_GROUP;
+
+/// Reseed the [`RunRng`] for a new run (picking a new seed first if rerolling).
+pub fn seed_run(
+    mut run_seed: ResMut<RunSeed>,
+    mut run_rng: ResMut<RunRng>,
+    replay_res: Res<ReplayResource>,
+) {
+    if replay_res.mode == ReplayMode::Playback {
+        run_seed.seed = replay_res.replay.seed;
+    } else if run_seed.reroll {
+        run_seed.seed = rand::random();
+    }
+    run_rng.0 = ChaCha8Rng::seed_from_u64(run_seed.seed);
+    info!("Starting run with seed: {}", run_seed.seed);
+}
+
 /// set up a simple 3D scene
 pub fn scene_setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut glowmaterials: ResMut<Assets<GlowyMaterial>>,
+    tuning: Res<GameTuning>,
 ) {
     // ground...
-    let Some(ground_ent) = spawn_ground(&mut commands, &mut meshes, &mut materials) else {
+    let Some(ground_ent) = spawn_ground(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &tuning,
+        GroundIndex(0),
+        GroundLayout::initial(&tuning),
+    ) else {
         return;
     };
     // rotate by 45 deg...
     commands
         .entity(ground_ent)
         .insert(TransformBundle::from_transform(Transform::from_rotation(
-            Quat::from_axis_angle(Vec3::X, GROUND_ANGLE),
+            Quat::from_axis_angle(Vec3::X, tuning.ground_angle),
         )));
     // de-spawn sensor(s)...
     let game_over_sensor_mesh: Mesh = shape::Box::new(
-        GROUND_WIDTH * 1.5,
-        GROUND_THICKNESS * 20.,
-        GROUND_LENGTH * 1.5,
+        tuning.ground_width * 1.5,
+        tuning.ground_thickness * 20.,
+        tuning.ground_length * 1.5,
     )
     .into();
     let Some(game_over_sen_collider) =
@@ -59,7 +94,7 @@ pub fn scene_setup(
     ));
 
     // ball...
-    let glowy_mat_hdl = glowmaterials.add(GlowyMaterial { env_texture: None });
+    let glowy_mat_hdl = glowmaterials.add(GlowyMaterial::default());
     let ball_mesh = Mesh::from(shape::UVSphere {
         radius: 0.5,
         ..default()
@@ -80,7 +115,12 @@ pub fn scene_setup(
         ball_collider,
         RigidBody::Dynamic,
         RollingBall,
+        // kept out of the fragments of disintegrating grounds...
+        CollisionGroups::new(Group::ALL.difference(FRAGMENTS_COLLISION_GROUP), Group::ALL),
         Velocity::default(),
+        ExternalImpulse::default(),
+        ActiveEvents::COLLISION_EVENTS | ActiveEvents::CONTACT_FORCE_EVENTS,
+        ContactForceEventThreshold(CAMERA_SHAKE_FORCE_THRESHOLD),
         TransformInterpolation::default(),
     ));
 
@@ -109,23 +149,38 @@ pub fn scene_setup(
     )
     .looking_at(Vec3::Z * 5., Vec3::Y);
     // camera...
-    commands.spawn((
-        Camera3dBundle {
-            transform: cam_transform,
-            ..default()
-        },
-        MyCamera {
-            init_transform: cam_transform,
-        },
-    ));
+    let _cam_ent = commands
+        .spawn((
+            Camera3dBundle {
+                transform: cam_transform,
+                ..default()
+            },
+            MyCamera {
+                init_transform: cam_transform,
+            },
+            CameraShake::default(),
+        ))
+        .id();
+    // cycled to/from the fly cam...
+    #[cfg(feature = "dev")]
+    commands.entity(_cam_ent).insert(TrackableCamera);
+}
+
+/// Start the [`SceneLoadingTimer`] of [`GameTuning::scene_loading_delay_secs`].
+pub fn start_scene_loading_timer(
+    mut loading_timer: ResMut<SceneLoadingTimer>,
+    tuning: Res<GameTuning>,
+) {
+    loading_timer.0 = Timer::from_seconds(tuning.scene_loading_delay_secs, TimerMode::Once);
 }
 
 pub fn move_to_in_game(
     mut next_state: ResMut<NextState<GameState>>,
     time: Res<Time>,
+    mut loading_timer: ResMut<SceneLoadingTimer>,
     mut rapier_conf: ResMut<RapierConfiguration>,
 ) {
-    if time.elapsed_seconds() < 3.0 {
+    if !loading_timer.0.tick(time.delta()).finished() {
         return;
     }
     // activate physics
@@ -138,7 +193,18 @@ pub fn spawn_ground(
     commands: &mut Commands<'_, '_>,
     meshes: &mut ResMut<'_, Assets<Mesh>>,
     materials: &mut ResMut<'_, Assets<StandardMaterial>>,
+    tuning: &GameTuning,
+    index: GroundIndex,
+    layout: GroundLayout,
 ) -> Option<Entity> {
+    let GameTuning {
+        ground_length,
+        ground_thickness,
+        ground_hazard_size,
+        ..
+    } = *tuning;
+    let GroundLayout { width, .. } = layout;
+    let hazards = layout.hazards.clone();
     let ground_ent = commands
         .spawn_empty()
         .insert(VisibilityBundle {
@@ -148,11 +214,16 @@ pub fn spawn_ground(
         .id();
     commands
         .entity(ground_ent)
-        .insert((Ground, BelongsToGround(ground_ent), Ccd::default()))
+        .insert((
+            Ground,
+            BelongsToGround(ground_ent),
+            Ccd::default(),
+            index,
+            layout,
+        ))
         .with_children(|commands| {
             // main ground mesh...
-            let ground_mesh: Mesh =
-                shape::Box::new(GROUND_WIDTH, GROUND_THICKNESS, GROUND_LENGTH).into();
+            let ground_mesh: Mesh = shape::Box::new(width, ground_thickness, ground_length).into();
             let Some(ground_collider) =
                 Collider::from_bevy_mesh(&ground_mesh, &ComputedColliderShape::TriMesh)
             else {
@@ -185,7 +256,7 @@ pub fn spawn_ground(
             ));
             // mid sensor...
             let ground_mid_sensor_mesh: Mesh =
-                shape::Box::new(GROUND_WIDTH, GROUND_THICKNESS * 4.0, GROUND_LENGTH * 0.1).into();
+                shape::Box::new(width, ground_thickness * 4.0, ground_length * 0.1).into();
             let Some(ground_mid_collider) =
                 Collider::from_bevy_mesh(&ground_mid_sensor_mesh, &ComputedColliderShape::TriMesh)
             else {
@@ -196,7 +267,7 @@ pub fn spawn_ground(
                     mesh: meshes.add(ground_mid_sensor_mesh.clone()),
                     material: materials.add(Color::rgb(0.3, 0.5, 0.9).into()),
                     transform: Transform::from_translation(
-                        Vec3::Y * GROUND_THICKNESS * 2.0 - Vec3::Z * GROUND_LENGTH * 0.2,
+                        Vec3::Y * ground_thickness * 2.0 - Vec3::Z * ground_length * 0.2,
                     ),
                     visibility: Visibility::Hidden,
                     ..default()
@@ -206,6 +277,31 @@ pub fn spawn_ground(
                 GroundMidSensor,
                 BelongsToGround(ground_ent),
             ));
+            // hazards...
+            let hazard_mesh: Mesh = shape::Cube::new(ground_hazard_size).into();
+            let hazard_collider = Collider::cuboid(
+                ground_hazard_size / 2.,
+                ground_hazard_size / 2.,
+                ground_hazard_size / 2.,
+            );
+            for hazard in hazards {
+                commands.spawn((
+                    PbrBundle {
+                        mesh: meshes.add(hazard_mesh.clone()),
+                        material: materials.add(Color::ORANGE_RED.into()),
+                        transform: Transform::from_xyz(
+                            hazard.x,
+                            (ground_thickness + ground_hazard_size) / 2.,
+                            hazard.y,
+                        ),
+                        ..default()
+                    },
+                    hazard_collider.clone(),
+                    RigidBody::Fixed,
+                    Hazard,
+                    BelongsToGround(ground_ent),
+                ));
+            }
         });
     Some(ground_ent)
 }
@@ -219,37 +315,114 @@ pub fn handle_scene_events(
     cameras: Query<Entity, With<MyCamera>>,
     game_over_sensor: Query<Entity, With<GameOverSensor>>,
     mut events: EventReader<SceneEvent>,
+    state: Res<State<GameState>>,
     mut next_state: ResMut<NextState<GameState>>,
     mut ground_res: ResMut<GroundsResource>,
-    scores_res: ResMut<ScoresResource>,
-    mut prev_scores_res: ResMut<PreviousScoresRes>,
+    mut scores_res: ResMut<ScoresResource>,
+    settings_res: Res<SettingsResource>,
+    run_seed: Res<RunSeed>,
+    mut high_scores_res: ResMut<HighScoresResource>,
+    mut replay_res: ResMut<ReplayResource>,
 ) {
     for event in events.iter() {
         match event {
-            SceneEvent::Start => {}
-            SceneEvent::Restart => {
-                // push to prev scores
-                prev_scores_res.0.push(scores_res.clone());
+            SceneEvent::Start => {
+                next_state.set(GameState::SceneLoading);
+            }
+            SceneEvent::GameOver => {
+                if *state.get() != GameState::InGame {
+                    continue;
+                }
+                // record to high scores (replays don't count)
+                if replay_res.mode == ReplayMode::Record {
+                    if let Some(time_survived_secs) = scores_res
+                        .stopwatch
+                        .as_ref()
+                        .map(|stopwatch| stopwatch.elapsed_secs())
+                    {
+                        let grounds_passed = scores_res.grounds_passed;
+                        // compared before recording, against the previous bests
+                        scores_res.was_new_best =
+                            high_scores_res.is_new_best(grounds_passed, time_survived_secs);
+                        high_scores_res.record(HighScore {
+                            time_survived_secs,
+                            grounds_passed,
+                            timestamp_secs: persistence::now_unix_secs(),
+                            seed: Some(run_seed.seed),
+                            settings: settings_res.as_ref().into(),
+                        });
+                    }
+                }
+                next_state.set(GameState::GameOver);
+            }
+            SceneEvent::Restart | SceneEvent::Quit => {
                 // reset any resources...
                 *ground_res = GroundsResource::default();
                 // mark for cleanup
-                for entity in balls.iter() {
-                    commands.entity(entity).insert(Cleanup::Recursive);
-                }
-                for entity in grounds.iter() {
-                    commands.entity(entity).insert(Cleanup::Recursive);
-                }
-                for entity in lights.iter() {
+                for entity in balls
+                    .iter()
+                    .chain(grounds.iter())
+                    .chain(lights.iter())
+                    .chain(cameras.iter())
+                    .chain(game_over_sensor.iter())
+                {
                     commands.entity(entity).insert(Cleanup::Recursive);
                 }
-                for entity in cameras.iter() {
-                    commands.entity(entity).insert(Cleanup::Recursive);
-                }
-                for entity in game_over_sensor.iter() {
-                    commands.entity(entity).insert(Cleanup::Recursive);
+                if let SceneEvent::Restart = event {
+                    next_state.set(GameState::SceneLoading);
+                } else {
+                    // stop any replay playback when leaving to menu
+                    replay_res.mode = ReplayMode::Record;
+                    next_state.set(GameState::Menu);
                 }
-                next_state.set(GameState::Menu);
             }
         }
     }
 }
+
+/// Freeze physics, eg. while paused or on game over.
+pub fn pause_physics(mut rapier_conf: ResMut<RapierConfiguration>) {
+    rapier_conf.physics_pipeline_active = false;
+}
+
+/// Un-freeze physics.
+pub fn resume_physics(mut rapier_conf: ResMut<RapierConfiguration>) {
+    rapier_conf.physics_pipeline_active = true;
+}
+
+/// Restart counting physics steps for a new run.
+pub fn reset_physics_steps(mut steps_res: ResMut<PhysicsStepsResource>) {
+    *steps_res = PhysicsStepsResource::default();
+}
+
+/// Count the physics steps simulated this frame into the [`PhysicsStepsResource`],
+/// to be run after the simulation stepped.
+pub fn count_physics_steps(
+    mut steps_res: ResMut<PhysicsStepsResource>,
+    rapier_conf: Res<RapierConfiguration>,
+    sim_to_render_time: Res<SimulationToRenderTime>,
+    time: Res<Time>,
+    mut prev_sim_to_render_diff: Local<f32>,
+) {
+    let (frame_steps, frame_secs) = if !rapier_conf.physics_pipeline_active {
+        (0, 0.)
+    } else {
+        match rapier_conf.timestep_mode {
+            TimestepMode::Fixed { dt, .. } => (1, dt),
+            TimestepMode::Variable {
+                max_dt, time_scale, ..
+            } => (1, (time.delta_seconds() * time_scale).min(max_dt)),
+            TimestepMode::Interpolated { dt, time_scale, .. } => {
+                // the frame time is added to the lag, each step then takes `dt` off it...
+                let stepped_secs =
+                    *prev_sim_to_render_diff + time.delta_seconds() - sim_to_render_time.diff;
+                let steps = (stepped_secs / dt.max(f32::EPSILON)).round().max(0.) as u32;
+                (steps, steps as f32 * dt * time_scale)
+            }
+        }
+    };
+    *prev_sim_to_render_diff = sim_to_render_time.diff;
+    steps_res.steps += frame_steps as u64;
+    steps_res.frame_steps = frame_steps;
+    steps_res.frame_secs = frame_secs;
+}
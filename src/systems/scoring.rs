@@ -1,27 +1,41 @@
+use std::time::Duration;
+
 use bevy::{prelude::*, time::Stopwatch};
 use bevy_egui::{
-    egui::{self, FontId, RichText},
+    egui::{self, Color32, FontId, RichText},
     EguiContexts,
 };
 use bevy_rapier3d::prelude::Velocity;
 
 use crate::{
-    components::RollingBall,
-    resources::{GroundsResource, PreviousScoresRes, ScoresResource},
+    components::{BelongsToGround, RollingBall, Wall},
+    resources::{
+        DifficultyResource, GroundsResource, HighScore, HighScoresResource, PhysicsStepsResource,
+        ScoresResource, SettingsResource, WallBudgetResource,
+    },
+    tuning::GameTuning,
 };
 
-use super::egui::get_default_egui_frame;
+use super::{egui::get_default_egui_frame, high_scores::format_date};
+
+/// Number of high scores listed on the scoreboard.
+const SCOREBOARD_HIGH_SCORES_COUNT: usize = 5;
 
 pub fn setup_scoring(mut scoring_res: ResMut<ScoresResource>, time: Res<Time>) {
     scoring_res.stopwatch = Some(Stopwatch::new());
     scoring_res.grounds_passed = 0;
+    scoring_res.was_new_best = false;
 }
 
-pub fn update_stopwatch(mut scoring_res: ResMut<ScoresResource>, time: Res<Time>) {
+/// Tick the run stopwatch by the simulated time, so a replay times the same as its run.
+pub fn update_stopwatch(
+    mut scoring_res: ResMut<ScoresResource>,
+    steps_res: Res<PhysicsStepsResource>,
+) {
     let Some(stopwatch) = scoring_res.stopwatch.as_mut() else {
         return;
     };
-    stopwatch.tick(time.delta());
+    stopwatch.tick(Duration::from_secs_f32(steps_res.frame_secs));
 }
 
 pub fn update_grounds_passed(
@@ -45,41 +59,60 @@ pub fn update_grounds_passed(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn display_scoreboard(
     query_ball: Query<&Velocity, With<RollingBall>>,
+    walls: Query<&BelongsToGround, With<Wall>>,
+    grounds_res: Res<GroundsResource>,
     scoring_res: Res<ScoresResource>,
-    prev_scoring_res: Res<PreviousScoresRes>,
+    high_scores_res: Res<HighScoresResource>,
+    difficulty_res: Res<DifficultyResource>,
+    budget_res: Res<WallBudgetResource>,
+    settings_res: Res<SettingsResource>,
+    tuning: Res<GameTuning>,
     mut egui_contexts: EguiContexts,
 ) {
+    if !settings_res.show_scoreboard {
+        return;
+    }
     let ScoresResource {
         stopwatch: Some(stopwatch),
         grounds_passed,
+        ..
     } = scoring_res.as_ref()
     else {
         return;
     };
-    let watch_display = format!(
-        "{:02.0}:{:02.0}",
-        stopwatch.elapsed_secs() / 60.,
-        stopwatch.elapsed_secs() % 60.
+    let score_display = format!(
+        "Time: {}  Panels: {}",
+        format_stopwatch(stopwatch.elapsed_secs()),
+        grounds_passed
     );
-    let score_display = format!("Time: {}  Panels: {}", watch_display, grounds_passed);
-    let prev_scores_display = prev_scoring_res.0.iter().filter_map(|scoring_res| {
-        let ScoresResource {
-            stopwatch: Some(stopwatch),
-            grounds_passed,
-        } = scoring_res
-        else {
-            return None;
-        };
-        let watch_display = format!(
-            "{:02.0}:{:02.0}",
-            stopwatch.elapsed_secs() / 60.,
-            stopwatch.elapsed_secs() % 60.
-        );
-        let score_display = format!("Time: {}  Panels: {}", watch_display, grounds_passed);
-        Some(score_display)
-    });
+    let best_time = high_scores_res.best_time_survived_secs();
+    let best_grounds_passed = high_scores_res.best_grounds_passed();
+    let is_new_best = high_scores_res.is_new_best(*grounds_passed, stopwatch.elapsed_secs());
+    let high_scores_display = high_scores_res
+        .entries
+        .iter()
+        .take(SCOREBOARD_HIGH_SCORES_COUNT)
+        .map(|entry| {
+            let HighScore {
+                time_survived_secs,
+                grounds_passed,
+                timestamp_secs,
+                ..
+            } = entry;
+            let score_display = format!(
+                "Time: {}  Panels: {}  ({})",
+                format_stopwatch(*time_survived_secs),
+                grounds_passed,
+                format_date(*timestamp_secs)
+            );
+            // highlight personal bests...
+            let is_best = Some(*grounds_passed) == best_grounds_passed
+                || Some(*time_survived_secs) == best_time;
+            (score_display, is_best)
+        });
     let Ok(ball_vel) = query_ball.get_single() else {
         return;
     };
@@ -107,14 +140,54 @@ pub fn display_scoreboard(
                             .size(17.),
                     );
                 });
+                if is_new_best {
+                    ui.label(RichText::new("New personal best!").color(Color32::GOLD));
+                }
+                ui.horizontal(|ui| {
+                    ui.label(format!("Difficulty: {:.0}%", difficulty_res.level * 100.));
+                    ui.separator();
+                    ui.label(format!(
+                        "Walls: {}/{}",
+                        walls
+                            .iter()
+                            .filter(
+                                |BelongsToGround(ground)| grounds_res.is_current_or_next(*ground)
+                            )
+                            .count(),
+                        difficulty_res.max_walls
+                    ));
+                    ui.separator();
+                    ui.label(format!(
+                        "Overheat: {:.1}s",
+                        difficulty_res.overheat_duration_secs
+                    ));
+                });
+                ui.horizontal(|ui| {
+                    let budget = &tuning.wall_budget;
+                    ui.label("Wall Budget:");
+                    ui.add(
+                        egui::ProgressBar::new(budget_res.charges / budget.max)
+                            .desired_width(120.)
+                            .text(format!("{:.1}/{:.0}", budget_res.charges, budget.max)),
+                    );
+                });
                 ui.separator();
-                if prev_scores_display.clone().count() > 0 {
-                    ui.label(RichText::new("Previous").heading());
-                    for score_display in prev_scores_display {
-                        ui.label(score_display);
+                if high_scores_display.clone().count() > 0 {
+                    ui.label(RichText::new("High Scores").heading());
+                    for (score_display, is_best) in high_scores_display {
+                        if is_best {
+                            ui.label(RichText::new(score_display).color(Color32::GOLD));
+                        } else {
+                            ui.label(score_display);
+                        }
                     }
                     ui.separator();
                 }
             });
         });
 }
+
+/// Format elapsed secs as `MM:SS`.
+fn format_stopwatch(elapsed_secs: f32) -> String {
+    format!("{:02.0}:{:02.0}", elapsed_secs / 60., elapsed_secs % 60.)
+}
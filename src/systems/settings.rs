@@ -1,17 +1,44 @@
-use bevy::prelude::*;
+use bevy::{
+    core_pipeline::fxaa::Fxaa,
+    prelude::*,
+    window::{PresentMode, PrimaryWindow},
+};
 use bevy_egui::{
     egui::{self, Slider},
-    EguiContexts,
+    EguiContexts, EguiSettings,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{MyCamera, MyLight},
+    constants::{INPUT_ACTIONS_STORAGE_KEY, SETTINGS_SCHEMA_VERSION, SETTINGS_STORAGE_KEY},
+    input::{InputAction, InputActions, InputBinding},
+    persistence,
+    resources::{AntiAliasing, CameraMode, SettingsResource},
 };
 
-use crate::resources::SettingsResource;
+#[cfg(feature = "dev")]
+use crate::plugins::FpsDisplaySettings;
 
 use super::egui::get_default_egui_frame;
 
 pub fn display_settings(
     mut settings_res: ResMut<SettingsResource>,
+    mut input_actions: ResMut<InputActions>,
+    key_input: Res<Input<KeyCode>>,
+    mouse_btn_input: Res<Input<MouseButton>>,
     mut egui_contexts: EguiContexts,
+    mut rebinding: Local<Option<InputAction>>,
 ) {
+    // rebind the action waiting for input to the first key/button pressed...
+    if let Some(action) = *rebinding {
+        if let Some(binding) = InputBinding::any_just_pressed(&key_input, &mouse_btn_input) {
+            input_actions.bindings.insert(action, binding);
+            *rebinding = None;
+        }
+    }
+    // edited on a copy, so the settings only change (and get saved/applied) on edits...
+    let mut settings = settings_res.clone();
     let frame = get_default_egui_frame();
     egui::Window::new("Settings")
         .title_bar(true)
@@ -23,11 +50,279 @@ pub fn display_settings(
         .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(0.0, 0.0))
         .show(egui_contexts.ctx_mut(), |ui| {
             ui.vertical(|ui| {
+                ui.label("Graphics");
+                ui.checkbox(&mut settings.shadows, "Shadows");
+                ui.horizontal(|ui| {
+                    ui.label("Anti-aliasing");
+                    for (anti_aliasing, label) in [
+                        (AntiAliasing::Off, "Off"),
+                        (AntiAliasing::Fxaa, "FXAA"),
+                        (AntiAliasing::Msaa4, "MSAA 4x"),
+                    ] {
+                        ui.selectable_value(&mut settings.anti_aliasing, anti_aliasing, label);
+                    }
+                });
+                ui.checkbox(&mut settings.vsync, "VSync");
+                ui.add(
+                    Slider::new(&mut settings.resolution_scale, 0.25..=1.).text("Resolution scale"),
+                );
+                ui.separator();
+                ui.label("Gameplay");
                 ui.add(
-                    Slider::new(&mut settings_res.wall_rotation_sensitivity, 0.0..=1.)
+                    Slider::new(&mut settings.wall_rotation_sensitivity, 0.0..=1.)
                         .clamp_to_range(false)
                         .text("Wall rotation sensitivity"),
-                )
+                );
+                ui.checkbox(&mut settings.show_ghost_preview, "Show wall preview");
+                ui.add(
+                    Slider::new(&mut settings.camera_follow_stiffness, 0.5..=10.)
+                        .text("Camera follow stiffness"),
+                );
+                ui.horizontal(|ui| {
+                    ui.label("Camera");
+                    for camera_mode in CameraMode::ALL {
+                        ui.selectable_value(
+                            &mut settings.camera_mode,
+                            camera_mode,
+                            camera_mode.label(),
+                        );
+                    }
+                });
+                ui.checkbox(&mut settings.camera_shake, "Camera shake");
+                ui.add_enabled(
+                    settings.camera_shake,
+                    Slider::new(&mut settings.camera_shake_strength, 0.0..=2.)
+                        .text("Camera shake strength"),
+                );
+                ui.separator();
+                ui.label("Audio");
+                ui.add(Slider::new(&mut settings.master_volume, 0.0..=1.).text("Master"));
+                ui.add(Slider::new(&mut settings.music_volume, 0.0..=1.).text("Music"));
+                ui.add(Slider::new(&mut settings.sfx_volume, 0.0..=1.).text("Sound effects"));
+                ui.checkbox(&mut settings.muted, "Mute");
+                ui.checkbox(&mut settings.mute_on_focus_loss, "Mute when in background");
+                ui.separator();
+                ui.label("HUD");
+                ui.checkbox(&mut settings.show_scoreboard, "Show scoreboard");
+                // fps overlay is dev tooling...
+                if cfg!(feature = "dev") {
+                    ui.checkbox(&mut settings.show_fps, "Show FPS");
+                }
+                ui.separator();
+                ui.label("Controls");
+                egui::Grid::new("input_actions").show(ui, |ui| {
+                    for action in InputAction::ALL {
+                        ui.label(action.label());
+                        let binding_display = if *rebinding == Some(action) {
+                            "Press a key...".to_string()
+                        } else {
+                            input_actions
+                                .binding(action)
+                                .map_or("Unbound".to_string(), |binding| binding.to_string())
+                        };
+                        if ui.button(binding_display).clicked() {
+                            *rebinding = Some(action);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if ui.button("Reset controls").clicked() {
+                    *input_actions = InputActions::default();
+                    *rebinding = None;
+                }
             });
         });
+    settings_res.set_if_neq(settings);
+}
+
+/// Apply the graphics/HUD settings, on change and to newly spawned lights/cameras.
+#[allow(clippy::too_many_arguments)]
+pub fn apply_settings(
+    mut commands: Commands,
+    settings_res: Res<SettingsResource>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut lights: Query<(Ref<MyLight>, &mut PointLight)>,
+    cameras: Query<(Entity, Ref<MyCamera>)>,
+    mut msaa: ResMut<Msaa>,
+    mut egui_settings: ResMut<EguiSettings>,
+    #[cfg(feature = "dev")] mut fps_display_settings: ResMut<FpsDisplaySettings>,
+) {
+    let is_changed = settings_res.is_changed();
+    for (my_light, mut point_light) in lights.iter_mut() {
+        if is_changed || my_light.is_added() {
+            point_light.shadows_enabled = settings_res.shadows;
+        }
+    }
+    for (cam_ent, my_camera) in cameras.iter() {
+        if !is_changed && !my_camera.is_added() {
+            continue;
+        }
+        if settings_res.anti_aliasing == AntiAliasing::Fxaa {
+            commands.entity(cam_ent).insert(Fxaa::default());
+        } else {
+            commands.entity(cam_ent).remove::<Fxaa>();
+        }
+    }
+    if !is_changed {
+        return;
+    }
+    let new_msaa = match settings_res.anti_aliasing {
+        AntiAliasing::Msaa4 => Msaa::Sample4,
+        AntiAliasing::Off | AntiAliasing::Fxaa => Msaa::Off,
+    };
+    if *msaa != new_msaa {
+        *msaa = new_msaa;
+    }
+    if let Ok(mut window) = windows.get_single_mut() {
+        window.present_mode = if settings_res.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+        let resolution_scale = settings_res.resolution_scale.clamp(0.1, 1.) as f64;
+        let scale_factor = window.resolution.base_scale_factor() * resolution_scale;
+        if window.resolution.scale_factor() != scale_factor {
+            window
+                .resolution
+                .set_scale_factor_override(Some(scale_factor));
+        }
+        // keep the UI the same size...
+        egui_settings.scale_factor = 1. / resolution_scale;
+    }
+    #[cfg(feature = "dev")]
+    {
+        fps_display_settings.visible = settings_res.show_fps;
+    }
+}
+
+/// Settings as saved, versioned so older saves keep loading.
+///
+/// Missing fields fall back to their defaults, so adding fields needs no version bump.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SavedSettings {
+    version: u32,
+    settings: SettingsResource,
+    input_actions: InputActions,
+}
+
+/// Load the saved [`SettingsResource`] and [`InputActions`] (if any),
+/// migrating them from older versions.
+pub fn load_settings(
+    mut settings_res: ResMut<SettingsResource>,
+    mut input_actions: ResMut<InputActions>,
+) {
+    let saved = match persistence::load_config(SETTINGS_STORAGE_KEY) {
+        Some(saved) => match ron::from_str::<SavedSettings>(&saved) {
+            Ok(saved) => saved,
+            Err(err) => {
+                warn!("Failed to parse saved settings: {}", err);
+                return;
+            }
+        },
+        // nothing saved yet, maybe by a version before the settings were saved...
+        None => SavedSettings::default(),
+    };
+    if saved.version > SETTINGS_SCHEMA_VERSION {
+        warn!(
+            "Settings were saved by a newer version ({}), loading what is known",
+            saved.version
+        );
+    }
+    let saved = if saved.version < SETTINGS_SCHEMA_VERSION {
+        let migrated = migrate_settings(saved, persistence::load);
+        // save right away, so the migration only runs once
+        store_settings(&migrated);
+        migrated
+    } else {
+        saved
+    };
+    *settings_res = saved.settings;
+    *input_actions = saved.input_actions;
+}
+
+/// Bring settings saved by an older version up to [`SETTINGS_SCHEMA_VERSION`],
+/// reading any data saved under older keys with `load`.
+///
+/// Older keys are only read, left for older builds to find.
+fn migrate_settings(
+    mut saved: SavedSettings,
+    load: impl Fn(&str) -> Option<String>,
+) -> SavedSettings {
+    // version `0`: input bindings were saved on their own...
+    if saved.version < 1 {
+        if let Some(input_actions) = load(INPUT_ACTIONS_STORAGE_KEY) {
+            match ron::from_str::<InputActions>(&input_actions) {
+                Ok(input_actions) => saved.input_actions = input_actions,
+                Err(err) => warn!("Failed to parse saved input bindings: {}", err),
+            }
+        }
+    }
+    saved.version = SETTINGS_SCHEMA_VERSION;
+    saved
+}
+
+/// Save the [`SettingsResource`] and [`InputActions`] whenever they change.
+pub fn save_settings(settings_res: Res<SettingsResource>, input_actions: Res<InputActions>) {
+    let is_changed = (settings_res.is_changed() && !settings_res.is_added())
+        || (input_actions.is_changed() && !input_actions.is_added());
+    if !is_changed {
+        return;
+    }
+    store_settings(&SavedSettings {
+        version: SETTINGS_SCHEMA_VERSION,
+        settings: settings_res.clone(),
+        input_actions: input_actions.clone(),
+    });
+}
+
+fn store_settings(saved: &SavedSettings) {
+    let serialized = match ron::to_string(saved) {
+        Ok(serialized) => serialized,
+        Err(err) => {
+            warn!("Failed to serialize settings: {}", err);
+            return;
+        }
+    };
+    if persistence::save_config(SETTINGS_STORAGE_KEY, &serialized).is_none() {
+        warn!("Failed to save settings");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    #[test]
+    fn migrates_input_bindings_saved_under_legacy_key() {
+        let mut legacy = InputActions::default();
+        legacy
+            .bindings
+            .insert(InputAction::Pause, InputBinding::Key(KeyCode::P));
+        let legacy_serialized = ron::to_string(&legacy).unwrap();
+        let loaded_keys = RefCell::new(vec![]);
+        let migrated = migrate_settings(SavedSettings::default(), |key| {
+            loaded_keys.borrow_mut().push(key.to_string());
+            (key == INPUT_ACTIONS_STORAGE_KEY).then(|| legacy_serialized.clone())
+        });
+        assert_eq!(migrated.version, SETTINGS_SCHEMA_VERSION);
+        assert_eq!(migrated.input_actions.bindings, legacy.bindings);
+        assert_eq!(*loaded_keys.borrow(), vec![INPUT_ACTIONS_STORAGE_KEY]);
+    }
+
+    #[test]
+    fn ignores_legacy_key_once_migrated() {
+        let saved = SavedSettings {
+            version: 1,
+            ..default()
+        };
+        let migrated = migrate_settings(saved, |key| panic!("loaded legacy key {key}"));
+        assert_eq!(migrated.version, SETTINGS_SCHEMA_VERSION);
+        assert_eq!(
+            migrated.input_actions.bindings,
+            InputActions::default().bindings
+        );
+    }
 }
@@ -0,0 +1,25 @@
+use bevy::prelude::*;
+
+use crate::tuning::{GameTuning, GameTuningHandle, GAME_TUNING_PATH};
+
+pub fn load_game_tuning(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(GameTuningHandle(asset_server.load(GAME_TUNING_PATH)));
+}
+
+/// Copy the tuning asset into the [`GameTuning`] resource whenever it (re)loads.
+pub fn apply_game_tuning(
+    mut asset_events: EventReader<AssetEvent<GameTuning>>,
+    tuning_assets: Res<Assets<GameTuning>>,
+    mut tuning_res: ResMut<GameTuning>,
+) {
+    for event in asset_events.iter() {
+        let (AssetEvent::Created { handle } | AssetEvent::Modified { handle }) = event else {
+            continue;
+        };
+        let Some(tuning) = tuning_assets.get(handle) else {
+            continue;
+        };
+        info!("Applying game tuning...");
+        *tuning_res = tuning.clone();
+    }
+}
@@ -0,0 +1,31 @@
+use bevy::prelude::*;
+
+use crate::{
+    resources::{PhysicsStepsResource, ScoresResource, WallBudgetResource},
+    tuning::GameTuning,
+};
+
+pub fn reset_wall_budget(mut budget_res: ResMut<WallBudgetResource>, tuning: Res<GameTuning>) {
+    *budget_res = WallBudgetResource {
+        charges: tuning.wall_budget.initial,
+        grounds_passed_recharged: 0,
+    };
+}
+
+/// Recharge the wall budget with the simulated time and for every panel passed,
+/// so a replay recharges the same as its run.
+pub fn recharge_wall_budget(
+    mut budget_res: ResMut<WallBudgetResource>,
+    scoring_res: Res<ScoresResource>,
+    steps_res: Res<PhysicsStepsResource>,
+    tuning: Res<GameTuning>,
+) {
+    let budget = &tuning.wall_budget;
+    let new_grounds_passed = scoring_res
+        .grounds_passed
+        .saturating_sub(budget_res.grounds_passed_recharged);
+    budget_res.grounds_passed_recharged = scoring_res.grounds_passed;
+    let recharge = budget.recharge_per_sec * steps_res.frame_secs
+        + budget.recharge_per_ground * new_grounds_passed as f32;
+    budget_res.charges = (budget_res.charges + recharge).min(budget.max);
+}
@@ -0,0 +1,182 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::{
+    input::{
+        mouse::{MouseScrollUnit, MouseWheel},
+        touch::Touch,
+    },
+    prelude::*,
+    window::{CursorLeft, CursorMoved, PrimaryWindow},
+};
+
+use crate::{
+    input::{ActionInput, InputAction},
+    resources::{SettingsResource, WallPlacementResource},
+};
+
+/// Pixels of (touchpad) scrolling per line.
+const SCROLL_PIXELS_PER_LINE: f32 = 100.;
+/// Max distance (in logical pixels) a touch can move and still count as a tap.
+const TAP_MAX_DISTANCE: f32 = 20.;
+/// Speed of the gamepad virtual cursor, in window heights per sec.
+const GAMEPAD_CURSOR_SPEED: f32 = 0.8;
+/// Stick deflection below which the virtual cursor does not move.
+const GAMEPAD_STICK_DEADZONE: f32 = 0.15;
+
+/// Feed the mouse and keyboard (through the [`InputActions`](crate::input::InputActions))
+/// into the [`WallPlacementResource`].
+///
+/// Runs first, resetting the per frame actions.
+pub fn handle_mouse_wall_input(
+    mut cursor_moved_events: EventReader<CursorMoved>,
+    mut cursor_left_events: EventReader<CursorLeft>,
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    action_input: ActionInput,
+    settings_res: Res<SettingsResource>,
+    mut placement_res: ResMut<WallPlacementResource>,
+    mut scroll: Local<f32>,
+) {
+    if let Some(event) = cursor_moved_events.iter().last() {
+        placement_res.pointer = Some(event.position);
+        placement_res.virtual_cursor = false;
+    }
+    if cursor_left_events.iter().count() > 0 {
+        placement_res.pointer = None;
+    }
+    // cycle wall kinds with the scroll wheel...
+    for event in mouse_wheel_events.iter() {
+        *scroll += match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / SCROLL_PIXELS_PER_LINE,
+        };
+    }
+    let steps = scroll.trunc();
+    if steps != 0. {
+        placement_res.kind = placement_res.kind.cycle(steps as i32);
+        *scroll -= steps;
+    }
+    if action_input.pressed(InputAction::RotateWallCcw) {
+        placement_res.angle += settings_res.wall_rotation_sensitivity;
+    } else if action_input.pressed(InputAction::RotateWallCw) {
+        placement_res.angle -= settings_res.wall_rotation_sensitivity;
+    }
+    placement_res.place = action_input.just_pressed(InputAction::PlaceWall);
+    placement_res.remove = action_input.just_pressed(InputAction::RemoveWall);
+}
+
+/// Touch gesture in progress.
+#[derive(Default)]
+pub struct TouchGesture {
+    /// Angle of the line between the two fingers of a twist, last frame.
+    twist_angle: Option<f32>,
+    /// Whether a second finger joined since all fingers were last lifted.
+    multi_touch: bool,
+}
+
+/// Feed touches into the [`WallPlacementResource`], reading [`Touches`]
+/// (so synthetic [`TouchInput`](bevy::input::touch::TouchInput) events work too).
+///
+/// One finger drags the preview around, a tap draws the wall where it landed
+/// and a two finger twist rotates the wall.
+pub fn handle_touch_wall_input(
+    touches: Res<Touches>,
+    mut placement_res: ResMut<WallPlacementResource>,
+    mut gesture: Local<TouchGesture>,
+) {
+    let mut active_touches = touches.iter().collect::<Vec<&Touch>>();
+    active_touches.sort_by_key(|touch| touch.id());
+    match active_touches.as_slice() {
+        [touch] if !gesture.multi_touch => {
+            placement_res.pointer = Some(touch.position());
+            placement_res.virtual_cursor = false;
+        }
+        [first, second] => {
+            gesture.multi_touch = true;
+            let twist = second.position() - first.position();
+            let twist_angle = twist.y.atan2(twist.x);
+            if let Some(prev_twist_angle) = gesture.twist_angle {
+                // screen y points down, so a clockwise twist is a clockwise rotation
+                let delta = (twist_angle - prev_twist_angle + PI).rem_euclid(TAU) - PI;
+                placement_res.angle -= delta;
+            }
+            gesture.twist_angle = Some(twist_angle);
+        }
+        _ => {
+            gesture.twist_angle = None;
+        }
+    }
+    for touch in touches.iter_just_released() {
+        if gesture.multi_touch || touch.distance().length() > TAP_MAX_DISTANCE {
+            continue;
+        }
+        placement_res.pointer = Some(touch.position());
+        placement_res.virtual_cursor = false;
+        placement_res.place = true;
+    }
+    if active_touches.is_empty() {
+        gesture.multi_touch = false;
+    }
+}
+
+/// Feed gamepads into the [`WallPlacementResource`].
+///
+/// The left stick moves a virtual cursor (starting at the window center), the
+/// shoulders/triggers rotate the wall, `A` draws it, `X` removes the pointed at
+/// wall and the D-pad cycles the wall kind.
+pub fn handle_gamepad_wall_input(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    buttons: Res<Input<GamepadButton>>,
+    settings_res: Res<SettingsResource>,
+    time: Res<Time>,
+    mut placement_res: ResMut<WallPlacementResource>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let window_size = Vec2::new(window.width(), window.height());
+    for gamepad in gamepads.iter() {
+        let axis = |axis_type| axes.get(GamepadAxis::new(gamepad, axis_type));
+        let button = |button_type| GamepadButton::new(gamepad, button_type);
+        // move the virtual cursor...
+        let stick = Vec2::new(
+            axis(GamepadAxisType::LeftStickX).unwrap_or(0.),
+            // screen y points down
+            -axis(GamepadAxisType::LeftStickY).unwrap_or(0.),
+        );
+        if stick.length() > GAMEPAD_STICK_DEADZONE {
+            let pointer = match placement_res.pointer {
+                Some(pointer) if placement_res.virtual_cursor => pointer,
+                _ => window_size / 2.,
+            };
+            let delta = stick * GAMEPAD_CURSOR_SPEED * window_size.y * time.delta_seconds();
+            placement_res.pointer = Some((pointer + delta).clamp(Vec2::ZERO, window_size));
+            placement_res.virtual_cursor = true;
+        }
+        // rotate...
+        if buttons.any_pressed([
+            button(GamepadButtonType::LeftTrigger),
+            button(GamepadButtonType::LeftTrigger2),
+        ]) {
+            placement_res.angle += settings_res.wall_rotation_sensitivity;
+        } else if buttons.any_pressed([
+            button(GamepadButtonType::RightTrigger),
+            button(GamepadButtonType::RightTrigger2),
+        ]) {
+            placement_res.angle -= settings_res.wall_rotation_sensitivity;
+        }
+        // cycle wall kinds...
+        if buttons.just_pressed(button(GamepadButtonType::DPadRight)) {
+            placement_res.kind = placement_res.kind.cycle(1);
+        } else if buttons.just_pressed(button(GamepadButtonType::DPadLeft)) {
+            placement_res.kind = placement_res.kind.cycle(-1);
+        }
+        if buttons.just_pressed(button(GamepadButtonType::South)) {
+            placement_res.place = true;
+        }
+        if buttons.just_pressed(button(GamepadButtonType::West)) {
+            placement_res.remove = true;
+        }
+    }
+}
@@ -1,39 +1,43 @@
+use std::f32::consts::FRAC_PI_2;
+
 use bevy::{
-    input::mouse::{MouseScrollUnit, MouseWheel},
     prelude::*,
-    window::PrimaryWindow,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
 };
 use bevy_rapier3d::prelude::*;
 
 use crate::{
     components::{
         BelongsToGround, Cleanup, Ground, GroundMesh, GroundMidSensor, GroundSurfaceSensor,
-        MyCamera, TempWall, Wall,
+        MyCamera, RollingBall, TempWall, Wall, WallKind,
     },
-    constants::{GROUND_LENGTH, GROUND_THICKNESS, GROUND_WIDTH},
     events::WallEvent,
-    resources::{GroundsResource, SettingsResource},
+    resources::{
+        DifficultyResource, GroundsResource, SettingsResource, WallBudgetResource,
+        WallPlacementResource,
+    },
+    tuning::GameTuning,
 };
 
+/// Distance from the camera the gamepad virtual cursor is drawn at.
+const VIRTUAL_CURSOR_DISTANCE: f32 = 10.;
+
 #[allow(clippy::too_many_arguments)]
 pub fn pick_ground_point_raycast(
-    windows: Query<&Window, With<PrimaryWindow>>,
     query_grounds: Query<&GlobalTransform, With<Ground>>,
     query_ground_meshes: Query<&BelongsToGround, (With<GroundMesh>, With<Collider>)>,
     temp_walls: Query<With<TempWall>>,
+    walls: Query<With<Wall>>,
     ground_res: Res<GroundsResource>,
-    settings_res: Res<SettingsResource>,
+    placement_res: Res<WallPlacementResource>,
+    tuning: Res<GameTuning>,
     rapier_context: Res<RapierContext>,
     cameras: Query<(&Camera, &GlobalTransform), With<MyCamera>>,
     mut gizmos: Gizmos,
-    mouse_btn_input: Res<Input<MouseButton>>,
-    key_input: Res<Input<KeyCode>>,
     mut wall_event: EventWriter<WallEvent>,
-    mut wall_angle: Local<f32>,
 ) {
-    let window = windows.single();
-
-    let Some(cursor_position) = window.cursor_position() else {
+    let Some(pointer_position) = placement_res.pointer else {
+        wall_event.send(WallEvent::HoverStop);
         return;
     };
 
@@ -46,10 +50,14 @@ pub fn pick_ground_point_raycast(
         return;
     };
 
-    // First, compute a ray from the mouse position.
-    let Some(ray) = camera.viewport_to_world(camera_transform, cursor_position) else {
+    // First, compute a ray from the pointer position.
+    let Some(ray) = camera.viewport_to_world(camera_transform, pointer_position) else {
         return;
     };
+    if placement_res.virtual_cursor {
+        let cursor_point = ray.origin + ray.direction * VIRTUAL_CURSOR_DISTANCE;
+        gizmos.circle(cursor_point, -ray.direction, 0.1, Color::WHITE);
+    }
 
     // Then cast the ray.
     let hit =
@@ -60,12 +68,21 @@ pub fn pick_ground_point_raycast(
         wall_event.send(WallEvent::HoverStop);
         return;
     };
+    // if hit a wall, it can be removed...
+    if walls.contains(entity) {
+        wall_event.send(WallEvent::HoverStop);
+        let RayIntersection { point, normal, .. } = intersection;
+        gizmos.circle(point, normal, 0.3, Color::RED);
+        if placement_res.remove {
+            wall_event.send(WallEvent::Remove { wall: entity });
+        }
+        return;
+    }
     // if hit continue to evaluate...
     let Ok(BelongsToGround(ground_ent)) = query_ground_meshes.get(entity) else {
         return;
     };
-    if ground_res.current_ground == Some(*ground_ent) || ground_res.next_ground == Some(*ground_ent)
-    {
+    if ground_res.is_current_or_next(*ground_ent) {
         let Ok(ground_transform) = query_grounds.get(*ground_ent) else {
             return;
         };
@@ -76,21 +93,17 @@ pub fn pick_ground_point_raycast(
         gizmos.circle(point, normal, 0.1, Color::CYAN);
         gizmos.circle(point, normal, 0.5, Color::CYAN);
 
-        if key_input.pressed(KeyCode::A) {
-            *wall_angle += settings_res.wall_rotation_sensitivity;
-        } else if key_input.pressed(KeyCode::D) {
-            *wall_angle -= settings_res.wall_rotation_sensitivity;
-        }
         let mut transform =
-            Transform::from_translation(point_local + Vec3::Y * GROUND_THICKNESS * 1.5);
-        transform.rotation = Quat::from_axis_angle(Vec3::Y, *wall_angle);
-        if mouse_btn_input.just_pressed(MouseButton::Left) {
+            Transform::from_translation(point_local + Vec3::Y * tuning.ground_thickness * 1.5);
+        transform.rotation = Quat::from_axis_angle(Vec3::Y, placement_res.angle);
+        // hover before drawing, so a tap draws where it landed
+        wall_event.send(WallEvent::HoverUpdate {
+            ground: *ground_ent,
+            transform,
+            kind: placement_res.kind,
+        });
+        if placement_res.place {
             wall_event.send(WallEvent::Draw);
-        } else {
-            wall_event.send(WallEvent::HoverUpdate {
-                ground: *ground_ent,
-                transform,
-            });
         }
     }
 }
@@ -106,79 +119,136 @@ pub fn handle_wall_events(
             &Parent,
             &Handle<Mesh>,
             &Handle<StandardMaterial>,
+            &WallKind,
         ),
         With<TempWall>,
     >,
+    walls: Query<&BelongsToGround, With<Wall>>,
     mut wall_events: EventReader<WallEvent>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    tuning: Res<GameTuning>,
+    difficulty_res: Res<DifficultyResource>,
+    mut budget_res: ResMut<WallBudgetResource>,
+    ground_res: Res<GroundsResource>,
+    settings_res: Res<SettingsResource>,
 ) {
+    // the temp wall, tracked here as one spawned (or despawned) by an event
+    // is only (un)queryable once the commands apply, after this frame...
+    let mut temp_wall =
+        temp_walls
+            .get_single()
+            .ok()
+            .map(|(entity, .., mesh_hdl, mat_hdl, kind)| TempWallState {
+                entity,
+                mesh_hdl: mesh_hdl.clone(),
+                mat_hdl: mat_hdl.clone(),
+                kind: *kind,
+                spawned: false,
+            });
     for event in wall_events.iter() {
         match event {
-            WallEvent::HoverUpdate { ground, transform } => {
+            WallEvent::HoverUpdate {
+                ground,
+                transform,
+                kind,
+            } => {
                 if commands.get_entity(*ground).is_none() {
                     continue;
                 }
-                if temp_walls.is_empty() {
-                    let Some(wall_ent) = draw_wall(
-                        &mut commands,
-                        &mut meshes,
-                        &mut materials,
-                        *transform,
-                        ground,
-                        false,
-                    ) else {
-                        continue;
-                    };
-                    commands.entity(wall_ent).insert(TempWall);
-                } else {
-                    let Ok((
+                match &temp_wall {
+                    Some(TempWallState {
                         entity,
-                        mut temp_wall_transform,
-                        mut visibility,
-                        parent,
-                        mesh_hdl,
-                        mat_hdl,
-                    )) = temp_walls.get_single_mut()
-                    else {
-                        continue;
-                    };
-                    *temp_wall_transform = *transform;
-                    *visibility = Visibility::Visible;
-                    let Some(wall_mat) = materials.get_mut(mat_hdl) else {
-                        continue;
-                    };
-                    wall_mat.alpha_mode = AlphaMode::Blend;
-                    wall_mat.base_color.set_a(0.6);
-                    if parent.get() != *ground {
-                        commands.entity(entity).remove_parent();
-                        commands.entity(*ground).push_children(&[entity]);
+                        kind: temp_kind,
+                        spawned: false,
+                        ..
+                    }) if temp_kind == kind => {
+                        let Ok((
+                            entity,
+                            mut temp_wall_transform,
+                            mut visibility,
+                            parent,
+                            ..,
+                            mat_hdl,
+                            _,
+                        )) = temp_walls.get_mut(*entity)
+                        else {
+                            continue;
+                        };
+                        *temp_wall_transform = *transform;
+                        *visibility = if settings_res.show_ghost_preview {
+                            Visibility::Visible
+                        } else {
+                            Visibility::Hidden
+                        };
+                        let Some(wall_mat) = materials.get_mut(mat_hdl) else {
+                            continue;
+                        };
+                        wall_mat.alpha_mode = AlphaMode::Blend;
+                        wall_mat.base_color.set_a(0.6);
+                        if parent.get() != *ground {
+                            commands.entity(entity).remove_parent();
+                            commands.entity(*ground).push_children(&[entity]);
+                        }
+                    }
+                    // swap the temp wall out when another kind is selected
+                    // (or it was only spawned this frame)...
+                    _ => {
+                        if let Some(TempWallState { entity, .. }) = temp_wall.take() {
+                            commands.entity(entity).despawn_recursive();
+                        }
+                        let Some((entity, mesh_hdl, mat_hdl)) = draw_wall(
+                            &mut commands,
+                            &mut meshes,
+                            &mut materials,
+                            &tuning,
+                            *kind,
+                            *transform,
+                            ground,
+                            false,
+                        ) else {
+                            continue;
+                        };
+                        commands.entity(entity).insert(TempWall);
+                        if !settings_res.show_ghost_preview {
+                            commands.entity(entity).insert(Visibility::Hidden);
+                        }
+                        temp_wall = Some(TempWallState {
+                            entity,
+                            mesh_hdl,
+                            mat_hdl,
+                            kind: *kind,
+                            spawned: true,
+                        });
                     }
                 }
             }
             WallEvent::HoverStop => {
-                let Ok((
-                    entity,
-                    mut temp_wall_transform,
-                    mut visibility,
-                    parent,
-                    mesh_hdl,
-                    mat_hdl,
-                )) = temp_walls.get_single_mut()
-                else {
+                let Some(TempWallState { entity, .. }) = &temp_wall else {
                     continue;
                 };
-                *visibility = Visibility::Hidden;
+                commands.entity(*entity).insert(Visibility::Hidden);
             }
             WallEvent::Draw => {
-                let Ok((
+                // walls on the grounds ahead are capped by difficulty
+                let walls_ahead = walls
+                    .iter()
+                    .filter(|BelongsToGround(ground)| ground_res.is_current_or_next(*ground))
+                    .count();
+                if walls_ahead >= difficulty_res.max_walls {
+                    continue;
+                }
+                // and by the wall budget
+                if budget_res.charges < tuning.wall_budget.wall_cost {
+                    continue;
+                }
+                let Some(TempWallState {
                     entity,
-                    mut temp_wall_transform,
-                    mut visibility,
-                    parent,
                     mesh_hdl,
                     mat_hdl,
-                )) = temp_walls.get_single_mut()
+                    kind,
+                    ..
+                }) = &temp_wall
                 else {
                     continue;
                 };
@@ -195,38 +265,105 @@ pub fn handle_wall_events(
                 };
                 wall_mat.alpha_mode = AlphaMode::Opaque;
                 wall_mat.base_color.set_a(1.);
+                // shown even if its preview was hidden
                 commands
-                    .entity(entity)
+                    .entity(*entity)
                     .insert(collider.clone())
+                    .insert(Visibility::Inherited)
                     .insert(Wall)
                     .remove::<TempWall>();
+                if *kind == WallKind::Bumper {
+                    commands
+                        .entity(*entity)
+                        .insert(Restitution::coefficient(1.));
+                }
+                budget_res.charges -= tuning.wall_budget.wall_cost;
+                // placed, the next hover spawns a new temp wall
+                temp_wall = None;
+            }
+            WallEvent::Remove { wall } => {
+                if !walls.contains(*wall) {
+                    continue;
+                }
+                commands.entity(*wall).despawn_recursive();
+                let budget = &tuning.wall_budget;
+                budget_res.charges = (budget_res.charges
+                    + budget.wall_cost * budget.refund_fraction)
+                    .min(budget.max);
             }
         }
     }
 }
 
+/// The temp wall (ghost preview) as of the wall events handled so far.
+struct TempWallState {
+    entity: Entity,
+    mesh_hdl: Handle<Mesh>,
+    mat_hdl: Handle<StandardMaterial>,
+    kind: WallKind,
+    /// Spawned by an event this frame, so not queryable yet.
+    spawned: bool,
+}
+
+/// Push the ball away from bumper walls as it hits them.
+pub fn handle_bumper_contacts(
+    mut balls: Query<(Entity, &GlobalTransform, &mut ExternalImpulse), With<RollingBall>>,
+    walls: Query<(Entity, &WallKind, &GlobalTransform), With<Wall>>,
+    tuning: Res<GameTuning>,
+    rapier_context: Res<RapierContext>,
+    mut touching_bumpers: Local<Vec<Entity>>,
+) {
+    let Ok((ball_ent, ball_transform, mut impulse)) = balls.get_single_mut() else {
+        return;
+    };
+    let prev_touching_bumpers = std::mem::take(&mut *touching_bumpers);
+    for (wall_ent, kind, wall_transform) in walls.iter() {
+        if *kind != WallKind::Bumper {
+            continue;
+        }
+        let is_touching = rapier_context
+            .contact_pair(ball_ent, wall_ent)
+            .is_some_and(|contact_pair| contact_pair.has_any_active_contacts());
+        if !is_touching {
+            continue;
+        }
+        touching_bumpers.push(wall_ent);
+        // only push once per hit
+        if prev_touching_bumpers.contains(&wall_ent) {
+            continue;
+        }
+        let push_dir = (ball_transform.translation() - wall_transform.translation())
+            .reject_from(wall_transform.up())
+            .normalize_or_zero();
+        impulse.impulse += push_dir * tuning.wall_bumper_impulse;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn draw_wall(
     commands: &mut Commands<'_, '_>,
     meshes: &mut ResMut<'_, Assets<Mesh>>,
     materials: &mut ResMut<'_, Assets<StandardMaterial>>,
+    tuning: &GameTuning,
+    kind: WallKind,
     transform: Transform,
     ground_ent: &Entity,
     add_collider: bool,
-) -> Option<Entity> {
-    let wall_x = GROUND_LENGTH / 3.5;
-    let wall_y = GROUND_THICKNESS * 3.;
-    let wall_z = GROUND_LENGTH * 0.01;
-    let wall: Mesh = shape::Box::new(wall_x, wall_y, wall_z).into();
+) -> Option<(Entity, Handle<Mesh>, Handle<StandardMaterial>)> {
+    let wall = wall_mesh(kind, tuning);
+    let mesh_hdl = meshes.add(wall.clone());
+    let mat_hdl = materials.add(wall_material(kind));
     let wall_ent = commands
         .spawn((
             PbrBundle {
-                mesh: meshes.add(wall.clone()),
-                material: materials.add(Color::rgb(1., 1., 1.).into()),
+                mesh: mesh_hdl.clone(),
+                material: mat_hdl.clone(),
                 transform,
                 ..default()
             },
             RigidBody::Fixed,
             BelongsToGround(*ground_ent),
+            kind,
         ))
         .id();
     if add_collider {
@@ -237,5 +374,168 @@ fn draw_wall(
         commands.entity(wall_ent).insert(collider.clone());
     }
     commands.entity(*ground_ent).push_children(&[wall_ent]);
-    Some(wall_ent)
+    Some((wall_ent, mesh_hdl, mat_hdl))
+}
+
+/// Mesh of a wall of the given kind, centered with its bottom on the ground.
+fn wall_mesh(kind: WallKind, tuning: &GameTuning) -> Mesh {
+    let wall_x = tuning.wall_length;
+    let wall_y = tuning.wall_height;
+    let wall_z = tuning.wall_thickness;
+    match kind {
+        WallKind::Short => shape::Box::new(wall_x, wall_y, wall_z).into(),
+        WallKind::Long => shape::Box::new(wall_x * 2., wall_y, wall_z).into(),
+        WallKind::Arc => arc_mesh(wall_x, FRAC_PI_2, wall_y, wall_z),
+        WallKind::Bumper => shape::Cylinder {
+            radius: wall_x * 0.2,
+            height: wall_y,
+            resolution: 16,
+            segments: 1,
+        }
+        .into(),
+        WallKind::Ramp => ramp_mesh(wall_x, wall_x * 0.6, wall_y, wall_y * 0.6),
+    }
+}
+
+fn wall_material(kind: WallKind) -> StandardMaterial {
+    match kind {
+        WallKind::Short => Color::rgb(1., 1., 1.).into(),
+        WallKind::Long => Color::rgb(0.8, 0.88, 1.).into(),
+        WallKind::Arc => Color::rgb(0.8, 1., 0.88).into(),
+        WallKind::Bumper => StandardMaterial {
+            base_color: Color::rgb(1., 0.55, 0.2),
+            emissive: Color::rgb(0.6, 0.2, 0.),
+            ..default()
+        },
+        WallKind::Ramp => Color::rgb(1., 0.92, 0.6).into(),
+    }
+}
+
+/// Arc of `angle` radians on a circle of `radius`, with its middle at the origin.
+fn arc_mesh(radius: f32, angle: f32, height: f32, thickness: f32) -> Mesh {
+    const SEGMENTS: usize = 8;
+    let dir = |a: f32| Vec3::new(a.sin(), 0., a.cos());
+    let point = |r: f32, a: f32, y: f32| r * dir(a) + Vec3::new(0., y, -radius);
+    let (inner, outer) = (radius - thickness / 2., radius + thickness / 2.);
+    let (bottom, top) = (-height / 2., height / 2.);
+    let mut builder = FlatMeshBuilder::default();
+    for i in 0..SEGMENTS {
+        let a0 = -angle / 2. + angle * i as f32 / SEGMENTS as f32;
+        let a1 = -angle / 2. + angle * (i + 1) as f32 / SEGMENTS as f32;
+        let mid = dir((a0 + a1) / 2.);
+        builder.quad(
+            [
+                point(outer, a0, bottom),
+                point(outer, a1, bottom),
+                point(outer, a1, top),
+                point(outer, a0, top),
+            ],
+            mid,
+        );
+        builder.quad(
+            [
+                point(inner, a0, bottom),
+                point(inner, a1, bottom),
+                point(inner, a1, top),
+                point(inner, a0, top),
+            ],
+            -mid,
+        );
+        builder.quad(
+            [
+                point(inner, a0, top),
+                point(inner, a1, top),
+                point(outer, a1, top),
+                point(outer, a0, top),
+            ],
+            Vec3::Y,
+        );
+        builder.quad(
+            [
+                point(inner, a0, bottom),
+                point(inner, a1, bottom),
+                point(outer, a1, bottom),
+                point(outer, a0, bottom),
+            ],
+            Vec3::NEG_Y,
+        );
+    }
+    // end caps...
+    for (a, side) in [(-angle / 2., -1.), (angle / 2., 1.)] {
+        let tangent = Vec3::new(a.cos(), 0., -a.sin());
+        builder.quad(
+            [
+                point(inner, a, bottom),
+                point(outer, a, bottom),
+                point(outer, a, top),
+                point(inner, a, top),
+            ],
+            tangent * side,
+        );
+    }
+    builder.build()
+}
+
+/// Wedge `width` wide and `depth` deep, rising by `rise` towards -Z.
+fn ramp_mesh(width: f32, depth: f32, height: f32, rise: f32) -> Mesh {
+    let (x, z) = (width / 2., depth / 2.);
+    let (bottom, top) = (-height / 2., -height / 2. + rise);
+    let front_left = Vec3::new(-x, bottom, z);
+    let front_right = Vec3::new(x, bottom, z);
+    let back_left = Vec3::new(-x, bottom, -z);
+    let back_right = Vec3::new(x, bottom, -z);
+    let top_left = Vec3::new(-x, top, -z);
+    let top_right = Vec3::new(x, top, -z);
+    let mut builder = FlatMeshBuilder::default();
+    builder.quad(
+        [front_left, front_right, back_right, back_left],
+        Vec3::NEG_Y,
+    );
+    builder.quad(
+        [front_left, front_right, top_right, top_left],
+        Vec3::new(0., depth, rise),
+    );
+    builder.quad([back_left, back_right, top_right, top_left], Vec3::NEG_Z);
+    builder.triangle([front_left, back_left, top_left], Vec3::NEG_X);
+    builder.triangle([front_right, back_right, top_right], Vec3::X);
+    builder.build()
+}
+
+/// Builds flat shaded meshes out of faces, wound to face outwards.
+#[derive(Default)]
+struct FlatMeshBuilder {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+}
+
+impl FlatMeshBuilder {
+    fn triangle(&mut self, mut vertices: [Vec3; 3], outward: Vec3) {
+        let mut normal = (vertices[1] - vertices[0])
+            .cross(vertices[2] - vertices[0])
+            .normalize_or_zero();
+        if normal.dot(outward) < 0. {
+            vertices.swap(1, 2);
+            normal = -normal;
+        }
+        for vertex in vertices {
+            self.positions.push(vertex.into());
+            self.normals.push(normal.into());
+        }
+    }
+
+    /// Quad with its vertices in order around its edge.
+    fn quad(&mut self, [a, b, c, d]: [Vec3; 4], outward: Vec3) {
+        self.triangle([a, b, c], outward);
+        self.triangle([a, c, d], outward);
+    }
+
+    fn build(self) -> Mesh {
+        let vertex_count = self.positions.len();
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; vertex_count]);
+        mesh.set_indices(Some(Indices::U32((0..vertex_count as u32).collect())));
+        mesh
+    }
 }
@@ -0,0 +1,190 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{
+    GROUND_ANGLE, GROUND_ANGLE_VARIATION, GROUND_HAZARDS_MAX, GROUND_HAZARD_SIZE,
+    GROUND_LATERAL_OFFSET_MAX, GROUND_LENGTH, GROUND_OVERHEAT_DURATION_SECS, GROUND_THICKNESS,
+    GROUND_WIDTH, GROUND_WIDTH_MIN,
+};
+
+/// Path of the tuning asset, relative to the assets dir.
+pub const GAME_TUNING_PATH: &str = "tuning/game.tuning.ron";
+
+/// # Game Tuning
+///
+/// Gameplay numbers, loaded from [`GAME_TUNING_PATH`] and hot reloaded on change.
+/// Also kept as a resource holding the latest loaded values, with defaults
+/// from the constants until the asset loads.
+#[derive(Debug, Clone, Resource, Reflect, Serialize, Deserialize, TypeUuid)]
+#[uuid = "3c6c0a7e-7f3b-4a52-9a0e-5a8b8d2f1c47"]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct GameTuning {
+    /// Base slope of the grounds around the X axis.
+    pub ground_angle: f32,
+    pub ground_length: f32,
+    pub ground_width: f32,
+    pub ground_thickness: f32,
+    /// Length consecutive grounds overlap by.
+    pub ground_overlap: f32,
+    pub ground_overheat_duration_secs: f32,
+    /// Time an overheated ground takes to break apart and fade out.
+    pub disintegration_secs: f32,
+    /// Fragments (per side) an overheated ground breaks into.
+    pub disintegration_fragments: usize,
+    /// Max fraction the angle of a spawned ground can vary from `ground_angle`.
+    pub ground_angle_variation: f32,
+    pub ground_lateral_offset_max: f32,
+    pub ground_width_min: f32,
+    pub ground_hazards_max: usize,
+    pub ground_hazard_size: f32,
+    pub wall_length: f32,
+    pub wall_height: f32,
+    pub wall_thickness: f32,
+    /// Impulse a bumper wall gives the ball on contact.
+    pub wall_bumper_impulse: f32,
+    /// Delay before the game starts once the scene is loaded.
+    pub scene_loading_delay_secs: f32,
+    pub difficulty: DifficultyCurve,
+    pub wall_budget: WallBudgetTuning,
+}
+
+impl Default for GameTuning {
+    fn default() -> Self {
+        Self {
+            ground_angle: GROUND_ANGLE,
+            ground_length: GROUND_LENGTH,
+            ground_width: GROUND_WIDTH,
+            ground_thickness: GROUND_THICKNESS,
+            ground_overlap: 0.2,
+            ground_overheat_duration_secs: GROUND_OVERHEAT_DURATION_SECS,
+            disintegration_secs: 1.5,
+            disintegration_fragments: 4,
+            ground_angle_variation: GROUND_ANGLE_VARIATION,
+            ground_lateral_offset_max: GROUND_LATERAL_OFFSET_MAX,
+            ground_width_min: GROUND_WIDTH_MIN,
+            ground_hazards_max: GROUND_HAZARDS_MAX,
+            ground_hazard_size: GROUND_HAZARD_SIZE,
+            wall_length: GROUND_LENGTH / 3.5,
+            wall_height: GROUND_THICKNESS * 3.,
+            wall_thickness: GROUND_LENGTH * 0.01,
+            wall_bumper_impulse: 3.0,
+            scene_loading_delay_secs: 3.0,
+            difficulty: DifficultyCurve::default(),
+            wall_budget: WallBudgetTuning::default(),
+        }
+    }
+}
+
+/// How the difficulty ramps up as a run goes on.
+///
+/// Difficulty goes from `0` to `1`, whichever of panels passed or time survived
+/// is further along. Each `*_scale` is the multiplier applied at max difficulty,
+/// the ground ones by the index of the ground only (see [`DifficultyCurve::ground_level`]).
+#[derive(Debug, Clone, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DifficultyCurve {
+    /// Panels passed to reach max difficulty.
+    pub grounds_to_max: f32,
+    /// Secs survived to reach max difficulty.
+    pub secs_to_max: f32,
+    /// Shape of the curve, `1` is linear, higher ramps up later.
+    pub exponent: f32,
+    pub overheat_duration_scale: f32,
+    pub ground_angle_scale: f32,
+    pub ground_width_scale: f32,
+    /// Max walls standing on the current and next ground at once,
+    /// at min and max difficulty.
+    pub max_walls: (usize, usize),
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        Self {
+            grounds_to_max: 100.,
+            secs_to_max: 600.,
+            exponent: 1.5,
+            overheat_duration_scale: 0.5,
+            ground_angle_scale: 1.5,
+            ground_width_scale: 0.6,
+            max_walls: (12, 4),
+        }
+    }
+}
+
+impl DifficultyCurve {
+    /// Difficulty level (`0` to `1`) for the given progress in a run.
+    pub fn level(&self, grounds_passed: u64, elapsed_secs: f32) -> f32 {
+        let progress = (grounds_passed as f32 / self.grounds_to_max.max(f32::EPSILON))
+            .max(elapsed_secs / self.secs_to_max.max(f32::EPSILON));
+        self.curve(progress)
+    }
+
+    /// Difficulty level (`0` to `1`) of the layout of the ground at `ground_index`.
+    /// Only depends on the index, so a seed always generates the same grounds.
+    pub fn ground_level(&self, ground_index: usize) -> f32 {
+        self.curve(ground_index as f32 / self.grounds_to_max.max(f32::EPSILON))
+    }
+
+    fn curve(&self, progress: f32) -> f32 {
+        progress.clamp(0., 1.).powf(self.exponent)
+    }
+}
+
+/// Budget walls are drawn from, see [`WallBudgetResource`](crate::resources::WallBudgetResource).
+#[derive(Debug, Clone, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WallBudgetTuning {
+    pub initial: f32,
+    pub max: f32,
+    pub recharge_per_sec: f32,
+    pub recharge_per_ground: f32,
+    /// Cost of drawing a wall.
+    pub wall_cost: f32,
+    /// Fraction of the cost refunded when removing a wall.
+    pub refund_fraction: f32,
+}
+
+impl Default for WallBudgetTuning {
+    fn default() -> Self {
+        Self {
+            initial: 3.,
+            max: 5.,
+            recharge_per_sec: 0.1,
+            recharge_per_ground: 1.,
+            wall_cost: 1.,
+            refund_fraction: 0.5,
+        }
+    }
+}
+
+/// Loads [`GameTuning`] from `*.tuning.ron` files.
+#[derive(Default)]
+pub struct GameTuningLoader;
+
+impl AssetLoader for GameTuningLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let tuning = ron::de::from_bytes::<GameTuning>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(tuning));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+/// Handle keeping the tuning asset loaded (and watched).
+#[derive(Debug, Resource)]
+pub struct GameTuningHandle(pub Handle<GameTuning>);
@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+use keep_it_rolling_game::{
+    components::{GroundIndex, GroundLayout},
+    resources::RunRng,
+    tuning::GameTuning,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+const GROUNDS: usize = 32;
+
+/// Layouts of the grounds after the first one, generated in order as in a run.
+fn layouts(seed: u64) -> Vec<(f32, f32, f32, Vec<Vec2>)> {
+    let tuning = GameTuning::default();
+    let mut run_rng = RunRng(ChaCha8Rng::seed_from_u64(seed));
+    (1..=GROUNDS)
+        .map(|index| {
+            let GroundLayout {
+                angle,
+                lateral_offset,
+                width,
+                hazards,
+            } = GroundLayout::generate(&mut run_rng.0, &tuning, GroundIndex(index));
+            (angle, lateral_offset, width, hazards)
+        })
+        .collect()
+}
+
+#[test]
+fn same_seed_generates_same_layouts() {
+    assert_eq!(layouts(42), layouts(42));
+}
+
+#[test]
+fn different_seed_generates_different_layouts() {
+    assert_ne!(layouts(42), layouts(43));
+}
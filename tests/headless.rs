@@ -0,0 +1,211 @@
+use std::time::Duration;
+
+use bevy::{
+    gizmos::GizmoPlugin,
+    input::{
+        touch::{TouchInput, TouchPhase},
+        InputPlugin,
+    },
+    prelude::*,
+    render::{
+        camera::{camera_system, ManualTextureViews},
+        render_resource::Shader,
+    },
+    scene::ScenePlugin,
+    time::TimeUpdateStrategy,
+};
+use bevy_rapier3d::prelude::*;
+use keep_it_rolling_game::{
+    components::{RollingBall, Wall, WallKind},
+    events::WallEvent,
+    resources::{GroundsResource, PhysicsStepsResource, ReplayResource, ScoresResource},
+    state::GameState,
+    KeepItRollingCorePlugin, KeepItRollingInputPlugin,
+};
+
+const DT: f32 = 1.0 / 60.0;
+
+/// Headless app with the core plugin, stepped with a fixed timestep.
+fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        ScenePlugin,
+        TransformPlugin,
+        HierarchyPlugin,
+        KeepItRollingCorePlugin {
+            timestep_mode: TimestepMode::Fixed {
+                dt: DT,
+                substeps: 1,
+            },
+            persistence: false,
+            auto_start: true,
+            disintegration: false,
+        },
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+        DT,
+    )));
+    app
+}
+
+/// [`headless_app`] with the wall input, over a window that is never opened.
+fn headless_input_app() -> App {
+    let mut app = headless_app();
+    app.add_plugins((WindowPlugin::default(), InputPlugin))
+        // what picking needs from the renderer: gizmos and the camera's viewport...
+        .add_asset::<Shader>()
+        .add_asset::<Image>()
+        .init_resource::<ManualTextureViews>()
+        .add_plugins(GizmoPlugin)
+        .add_systems(PostUpdate, camera_system::<Projection>)
+        .add_plugins(KeepItRollingInputPlugin);
+    app
+}
+
+fn step(app: &mut App, updates: usize) {
+    for _ in 0..updates {
+        app.update();
+    }
+}
+
+/// Step past the scene loading delay, into the run.
+fn start_run(app: &mut App) {
+    step(app, 5 * 60);
+    assert_eq!(
+        *app.world.resource::<State<GameState>>().get(),
+        GameState::InGame
+    );
+}
+
+fn wall_kinds(app: &mut App) -> Vec<WallKind> {
+    app.world
+        .query_filtered::<&WallKind, With<Wall>>()
+        .iter(&app.world)
+        .copied()
+        .collect()
+}
+
+#[test]
+fn run_starts_and_scores_headless() {
+    let mut app = headless_app();
+    start_run(&mut app);
+    step(&mut app, 5 * 60);
+    let scores = app.world.resource::<ScoresResource>();
+    let stopwatch = scores.stopwatch.as_ref().expect("run stopwatch started");
+    assert!(stopwatch.elapsed_secs() > 0.);
+}
+
+#[test]
+fn draws_wall_of_kind_swapped_to_in_same_frame() {
+    let mut app = headless_app();
+    start_run(&mut app);
+    let ground = app
+        .world
+        .resource::<GroundsResource>()
+        .current_ground
+        .expect("current ground");
+    let transform = Transform::from_xyz(2., 0.5, 0.);
+    app.world.send_event(WallEvent::HoverUpdate {
+        ground,
+        transform,
+        kind: WallKind::Short,
+    });
+    app.update();
+    app.world.send_event(WallEvent::HoverUpdate {
+        ground,
+        transform,
+        kind: WallKind::Bumper,
+    });
+    app.world.send_event(WallEvent::Draw);
+    app.update();
+    assert_eq!(wall_kinds(&mut app), vec![WallKind::Bumper]);
+}
+
+#[test]
+fn touch_tap_draws_wall() {
+    let mut app = headless_input_app();
+    start_run(&mut app);
+    let ground = app
+        .world
+        .resource::<GroundsResource>()
+        .current_ground
+        .expect("current ground");
+    let ground_translation = app
+        .world
+        .get::<GlobalTransform>(ground)
+        .expect("ground transform")
+        .translation();
+    let (camera, camera_transform) = app
+        .world
+        .query::<(&Camera, &GlobalTransform)>()
+        .single(&app.world);
+    let position = camera
+        .world_to_viewport(camera_transform, ground_translation)
+        .expect("ground in view");
+    for phase in [TouchPhase::Started, TouchPhase::Ended] {
+        app.world.send_event(TouchInput {
+            phase,
+            position,
+            force: None,
+            id: 0,
+        });
+        app.update();
+    }
+    assert_eq!(wall_kinds(&mut app), vec![WallKind::default()]);
+}
+
+/// Grounds passed and ball position, to compare runs.
+fn run_outcome(app: &mut App) -> (u64, Vec3) {
+    let ball_translation = app
+        .world
+        .query_filtered::<&Transform, With<RollingBall>>()
+        .single(&app.world)
+        .translation;
+    let grounds_passed = app.world.resource::<ScoresResource>().grounds_passed;
+    (grounds_passed, ball_translation)
+}
+
+#[test]
+fn replay_plays_back_its_run() {
+    // record a run, with a wall in the ball's way...
+    let mut app = headless_app();
+    start_run(&mut app);
+    let ground = app
+        .world
+        .resource::<GroundsResource>()
+        .current_ground
+        .expect("current ground");
+    app.world.send_event(WallEvent::HoverUpdate {
+        ground,
+        transform: Transform::from_xyz(0.5, 0.5, 2.).with_rotation(Quat::from_rotation_y(0.4)),
+        kind: WallKind::Long,
+    });
+    app.world.send_event(WallEvent::Draw);
+    app.update();
+    assert_eq!(wall_kinds(&mut app), vec![WallKind::Long]);
+    step(&mut app, 20 * 60);
+    let recorded_steps = app.world.resource::<PhysicsStepsResource>().steps;
+    let recorded_walls = wall_kinds(&mut app);
+    let recorded_outcome = run_outcome(&mut app);
+    let replay = app.world.resource::<ReplayResource>().replay.clone();
+    assert_eq!(replay.wall_draws.len(), 1);
+
+    // ...then play it back from scratch, up to the same physics step
+    let mut app = headless_app();
+    app.world.resource_mut::<ReplayResource>().play(replay);
+    start_run(&mut app);
+    for _ in 0..30 * 60 {
+        if app.world.resource::<PhysicsStepsResource>().steps >= recorded_steps {
+            break;
+        }
+        app.update();
+    }
+    assert_eq!(
+        app.world.resource::<PhysicsStepsResource>().steps,
+        recorded_steps
+    );
+    assert_eq!(wall_kinds(&mut app), recorded_walls);
+    assert_eq!(run_outcome(&mut app), recorded_outcome);
+}